@@ -0,0 +1,94 @@
+//! Debounced push button input
+
+use arduino_hal::port::{
+    mode::{Floating, Input, PullUp},
+    Pin, PinOps,
+};
+
+/// Default time in milliseconds a [`Button`]'s level must hold before a change is accepted
+pub const DEFAULT_DEBOUNCE_MS: u16 = 20;
+
+/// Push button wired between its pin and GND, using the internal pull-up
+///
+/// [`Button::poll`] should be called once per loop iteration; presses and releases are only
+/// reported once the pin has held its new level for the debounce interval, and each edge is
+/// queued to be reported exactly once
+#[must_use]
+pub struct Button<PIN> {
+    pin: Pin<Input<PullUp>, PIN>,
+    debounce_ms: u16,
+
+    raw_low: bool,
+    changed_at: u32,
+    held: bool,
+
+    queued_presses: u8,
+    queued_releases: u8,
+}
+
+impl<PIN> Button<PIN>
+where
+    PIN: PinOps,
+{
+    /// Bind button to pin with the given debounce interval
+    pub fn new(pin: Pin<Input<Floating>, PIN>, debounce_ms: u16) -> Self {
+        Self {
+            pin: pin.into_pull_up_input(),
+            debounce_ms,
+
+            raw_low: false,
+            changed_at: 0,
+            held: false,
+
+            queued_presses: 0,
+            queued_releases: 0,
+        }
+    }
+
+    /// Sample the pin and register any debounced press or release
+    pub fn poll(&mut self, now: u32) {
+        let raw_low = self.pin.is_low();
+
+        if raw_low != self.raw_low {
+            self.raw_low = raw_low;
+            self.changed_at = now;
+        } else if raw_low != self.held
+            && now.wrapping_sub(self.changed_at) >= self.debounce_ms as u32
+        {
+            self.held = raw_low;
+            if raw_low {
+                self.queued_presses = self.queued_presses.saturating_add(1);
+            } else {
+                self.queued_releases = self.queued_releases.saturating_add(1);
+            }
+        }
+    }
+
+    /// Checks if there are any queued presses, dequeuing one if so
+    pub const fn was_pressed(&mut self) -> bool {
+        match self.queued_presses {
+            0 => false,
+            p => {
+                self.queued_presses = p - 1;
+                true
+            }
+        }
+    }
+
+    /// Checks if there are any queued releases, dequeuing one if so
+    pub const fn was_released(&mut self) -> bool {
+        match self.queued_releases {
+            0 => false,
+            r => {
+                self.queued_releases = r - 1;
+                true
+            }
+        }
+    }
+
+    /// Checks if the button is currently held down
+    #[must_use]
+    pub const fn is_held(&self) -> bool {
+        self.held
+    }
+}
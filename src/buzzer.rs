@@ -0,0 +1,124 @@
+//! Non-blocking buzzer driver for audible alerts
+
+use arduino_hal::{
+    hal::port::PC6,
+    port::{mode::Output, Pin},
+};
+
+#[derive(Clone, Copy)]
+enum BuzzerState {
+    Silent,
+    Continuous,
+    Pattern {
+        on_ms: u16,
+        off_ms: u16,
+        /// Beeps left to sound, or `None` to repeat until silenced
+        remaining: Option<u8>,
+        sounding: bool,
+        since: u32,
+    },
+}
+
+/// Active buzzer driven directly from a digital output
+///
+/// Beep patterns are advanced by [`Buzzer::tick`] using the millisecond counter, so sounding the
+/// buzzer never stalls the control loop
+#[must_use]
+pub struct Buzzer {
+    pin: Pin<Output, PC6>,
+    state: BuzzerState,
+}
+
+impl Buzzer {
+    /// Bind buzzer to pin and initialize silent
+    pub fn new(mut pin: Pin<Output, PC6>) -> Self {
+        pin.set_low();
+        Self {
+            pin,
+            state: BuzzerState::Silent,
+        }
+    }
+
+    /// Checks if the buzzer is playing a pattern or sounding continuously
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        !matches!(self.state, BuzzerState::Silent)
+    }
+
+    /// Start a pattern of `count` beeps, each sounding for `on_ms` followed by `off_ms` of silence,
+    /// replacing whatever was playing before
+    pub fn beep(&mut self, now: u32, on_ms: u16, off_ms: u16, count: u8) {
+        if count == 0 {
+            self.silence();
+            return;
+        }
+
+        self.pin.set_high();
+        self.state = BuzzerState::Pattern {
+            on_ms,
+            off_ms,
+            remaining: Some(count),
+            sounding: true,
+            since: now,
+        };
+    }
+
+    /// Repeat beeps sounding for `on_ms` followed by `off_ms` of silence until silenced, replacing
+    /// whatever was playing before
+    pub fn beep_repeating(&mut self, now: u32, on_ms: u16, off_ms: u16) {
+        self.pin.set_high();
+        self.state = BuzzerState::Pattern {
+            on_ms,
+            off_ms,
+            remaining: None,
+            sounding: true,
+            since: now,
+        };
+    }
+
+    /// Sound the buzzer continuously until silenced
+    pub fn alarm(&mut self) {
+        self.pin.set_high();
+        self.state = BuzzerState::Continuous;
+    }
+
+    /// Stop any pattern or alarm
+    pub fn silence(&mut self) {
+        self.pin.set_low();
+        self.state = BuzzerState::Silent;
+    }
+
+    /// Advance the current beep pattern, if any
+    pub fn tick(&mut self, now: u32) {
+        let BuzzerState::Pattern {
+            on_ms,
+            off_ms,
+            ref mut remaining,
+            ref mut sounding,
+            ref mut since,
+        } = self.state
+        else {
+            return;
+        };
+
+        let elapsed = now.wrapping_sub(*since);
+        if *sounding {
+            if elapsed >= on_ms as u32 {
+                self.pin.set_low();
+                *sounding = false;
+                *since = now;
+            }
+        } else if elapsed >= off_ms as u32 {
+            if let Some(remaining) = remaining {
+                *remaining -= 1;
+                if *remaining == 0 {
+                    self.state = BuzzerState::Silent;
+                    return;
+                }
+            }
+            self.pin.set_high();
+            *sounding = true;
+            *since = now;
+        }
+    }
+}
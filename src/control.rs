@@ -1,5 +1,7 @@
 //! Abstractions for PWM-controlled devices
 
+use core::cmp::Ordering;
+
 use arduino_hal::{
     clock::Clock,
     hal::port::{PB5, PB6, PB7},
@@ -11,12 +13,19 @@ use arduino_hal::{
     DefaultClock,
 };
 
+use crate::utils::u16_to_f32;
+
 /// 3-channel PWM controller built atop [TC1]
 ///
 /// Output pins are:
 /// - [PB5]: channel A
 /// - [PB6]: channel B
 /// - [PB7]: channel C
+///
+/// The timer runs in phase and frequency correct mode with ICR1 as TOP, so every channel stays
+/// available for output. Duty changes are double-buffered and take effect at BOTTOM, but ICR1 is
+/// not buffered in any mode; the only mode that buffers TOP keeps it in OCR1A, at the cost of
+/// channel A. See [`PWMController::set_hz_seamless`] for changing the frequency without a glitch
 pub struct PWMController {
     tc1: TC1,
     _ch_a: Pin<Output, PB5>,
@@ -29,6 +38,16 @@ pub struct PWMController {
     duty_a: u16,
     duty_b: u16,
     duty_c: u16,
+
+    min_duty_a: u16,
+    min_duty_b: u16,
+    min_duty_c: u16,
+
+    slew: [u16; 3],
+    commanded: [u16; 3],
+
+    bang_bang: [Option<(u16, u16)>; 3],
+    bang_on: [bool; 3],
 }
 
 impl PWMController {
@@ -47,11 +66,12 @@ impl PWMController {
         let ch_b = d10.into_output();
         let ch_c = d11.into_output();
 
+        // Phase and frequency correct PWM, TOP = ICR1 (mode 8)
         tc1.tccr1a().write(|w| {
             w.com1a().match_clear();
             w.com1b().match_clear();
             w.com1c().match_clear();
-            w.wgm1().set(0b10)
+            w.wgm1().set(0b00)
         });
 
         tc1.tccr1b().write(|w| {
@@ -78,80 +98,519 @@ impl PWMController {
             duty_a: 0,
             duty_b: 0,
             duty_c: 0,
+
+            min_duty_a: 0,
+            min_duty_b: 0,
+            min_duty_c: 0,
+
+            slew: [0; 3],
+            commanded: [0; 3],
+
+            bang_bang: [None; 3],
+            bang_on: [false; 3],
         }
     }
 
     /// Change PWM frequency and reset timer to minimize interruptions
+    ///
+    /// Resetting the counter mid-pulse can stretch or truncate the current cycle; see
+    /// [`PWMController::set_hz_seamless`] to avoid that
     pub fn set_hz(&mut self, hz: u16) {
+        self.load_hz(hz);
+        self.tc1.tcnt1().reset();
+    }
+
+    /// Change PWM frequency at the start of a cycle, so no pulse is stretched or truncated
+    ///
+    /// Waits for the counter to reach BOTTOM, then updates TOP while the counter is still well
+    /// below it. The new duties are loaded at the following BOTTOM, so the single cycle in between
+    /// keeps the old compare values against the new TOP
+    ///
+    /// Note: interrupts are held off for up to one PWM period while waiting, so at frequencies
+    /// below ~1 kHz [`millis`](crate::millis::millis) may lose ticks; prefer
+    /// [`PWMController::set_hz`] there
+    pub fn set_hz_seamless(&mut self, hz: u16) {
+        avr_device::interrupt::free(|_| {
+            self.tc1.tifr1().write(|w| w.tov1().set_bit());
+            while self.tc1.tifr1().read().tov1().bit_is_clear() {}
+            self.load_hz(hz);
+        });
+    }
+
+    fn load_hz(&mut self, hz: u16) {
         self.hz = hz;
         self.top = (DefaultClock::FREQ / (self.hz as u32 * 2)) as u16;
 
         self.tc1.icr1().write(|w| w.set(self.top));
 
-        let da = ((self.top as u32 * self.duty_a as u32) >> 8) as u16;
-        let db = ((self.top as u32 * self.duty_b as u32) >> 8) as u16;
-        let dc = ((self.top as u32 * self.duty_c as u32) >> 8) as u16;
+        let da = self.compare(PwmChannel::A, self.duty_a);
+        let db = self.compare(PwmChannel::B, self.duty_b);
+        let dc = self.compare(PwmChannel::C, self.duty_c);
 
         self.tc1.ocr1a().write(|w| w.set(da));
         self.tc1.ocr1b().write(|w| w.set(db));
         self.tc1.ocr1c().write(|w| w.set(dc));
-
-        self.tc1.tcnt1().reset();
     }
 
-    /// Set PWM duty of channel A in the range `0..=256`
+    /// Set PWM duty of channel A in the range `0..=256`, immediately and regardless of slew rate
     ///
-    /// Values exceeding `256` will be clamped
+    /// Values exceeding `256` will be clamped, and nonzero values are then remapped onto the range
+    /// from the channel's minimum duty to `256`; see [`PWMController::set_min_duty_a`]
     pub fn set_duty_a(&mut self, duty: u16) {
         let duty = if duty > 256 { 256 } else { duty };
+        self.latch(PwmChannel::A, duty);
 
-        let d = ((self.top as u32 * duty as u32) >> 8) as u16;
+        let d = self.compare(PwmChannel::A, duty);
         self.tc1.ocr1a().write(|w| w.set(d));
 
         self.duty_a = duty;
+        self.commanded[PwmChannel::A as usize] = duty;
     }
 
-    /// Set PWM duty of channel B in the range `0..=256`
+    /// Set PWM duty of channel B in the range `0..=256`, immediately and regardless of slew rate
     ///
-    /// Values exceeding `256` will be clamped
+    /// Values exceeding `256` will be clamped, and nonzero values are then remapped onto the range
+    /// from the channel's minimum duty to `256`; see [`PWMController::set_min_duty_b`]
     pub fn set_duty_b(&mut self, duty: u16) {
         let duty = if duty > 256 { 256 } else { duty };
+        self.latch(PwmChannel::B, duty);
 
-        let d = ((self.top as u32 * duty as u32) >> 8) as u16;
+        let d = self.compare(PwmChannel::B, duty);
         self.tc1.ocr1b().write(|w| w.set(d));
 
         self.duty_b = duty;
+        self.commanded[PwmChannel::B as usize] = duty;
     }
 
-    /// Set PWM duty of channel C in the range `0..=256`
+    /// Set PWM duty of channel C in the range `0..=256`, immediately and regardless of slew rate
     ///
-    /// Values exceeding `256` will be clamped
+    /// Values exceeding `256` will be clamped, and nonzero values are then remapped onto the range
+    /// from the channel's minimum duty to `256`; see [`PWMController::set_min_duty_c`]
     pub fn set_duty_c(&mut self, duty: u16) {
         let duty = if duty > 256 { 256 } else { duty };
+        self.latch(PwmChannel::C, duty);
 
-        let d = ((self.top as u32 * duty as u32) >> 8) as u16;
+        let d = self.compare(PwmChannel::C, duty);
         self.tc1.ocr1c().write(|w| w.set(d));
 
         self.duty_c = duty;
+        self.commanded[PwmChannel::C as usize] = duty;
+    }
+
+    /// Set the minimum nonzero duty of channel A, for loads that stall below a certain duty
+    ///
+    /// Nonzero duties are remapped from `1..=256` onto `min_duty..=256`, so the load never sits in
+    /// its stall band while callers still work across the full range, and 0 still stops it
+    /// completely. Values exceeding `256` will be clamped. Takes effect on the next call to
+    /// [`PWMController::set_duty_a`]
+    pub const fn set_min_duty_a(&mut self, min_duty: u16) {
+        self.min_duty_a = if min_duty > 256 { 256 } else { min_duty };
     }
 
-    /// Gets PWM duty of channel A in the range `0..=256`
+    /// Set the minimum nonzero duty of channel B, for loads that stall below a certain duty
+    ///
+    /// Remaps duties as for [`PWMController::set_min_duty_a`], taking effect on the next call to
+    /// [`PWMController::set_duty_b`]
+    pub const fn set_min_duty_b(&mut self, min_duty: u16) {
+        self.min_duty_b = if min_duty > 256 { 256 } else { min_duty };
+    }
+
+    /// Set the minimum nonzero duty of channel C, for loads that stall below a certain duty
+    ///
+    /// Remaps duties as for [`PWMController::set_min_duty_a`], taking effect on the next call to
+    /// [`PWMController::set_duty_c`]
+    pub const fn set_min_duty_c(&mut self, min_duty: u16) {
+        self.min_duty_c = if min_duty > 256 { 256 } else { min_duty };
+    }
+
+    /// Set whether channel A's output is electrically inverted, for loads that run at full speed
+    /// on zero duty
+    ///
+    /// The duty set and reported remains the logical duty seen by the load
+    pub fn set_invert_a(&mut self, invert: bool) {
+        self.tc1.tccr1a().modify(|_, w| {
+            if invert {
+                w.com1a().match_set()
+            } else {
+                w.com1a().match_clear()
+            }
+        });
+    }
+
+    /// Set whether channel B's output is electrically inverted, for loads that run at full speed
+    /// on zero duty
+    ///
+    /// The duty set and reported remains the logical duty seen by the load
+    pub fn set_invert_b(&mut self, invert: bool) {
+        self.tc1.tccr1a().modify(|_, w| {
+            if invert {
+                w.com1b().match_set()
+            } else {
+                w.com1b().match_clear()
+            }
+        });
+    }
+
+    /// Set whether channel C's output is electrically inverted, for loads that run at full speed
+    /// on zero duty
+    ///
+    /// The duty set and reported remains the logical duty seen by the load
+    pub fn set_invert_c(&mut self, invert: bool) {
+        self.tc1.tccr1a().modify(|_, w| {
+            if invert {
+                w.com1c().match_set()
+            } else {
+                w.com1c().match_clear()
+            }
+        });
+    }
+
+    /// Gets PWM duty of channel A in the range `0..=256`, as set rather than as remapped onto the
+    /// channel's minimum duty
     #[must_use]
     pub const fn duty_a(&self) -> u16 {
         self.duty_a
     }
 
-    /// Gets PWM duty of channel B in the range `0..=256`
+    /// Gets PWM duty of channel B in the range `0..=256`, as set rather than as remapped onto the
+    /// channel's minimum duty
     #[must_use]
     pub const fn duty_b(&self) -> u16 {
         self.duty_b
     }
 
-    /// Gets PWM duty of channel C in the range `0..=256`
+    /// Gets PWM duty of channel C in the range `0..=256`, as set rather than as remapped onto the
+    /// channel's minimum duty
     #[must_use]
     pub const fn duty_c(&self) -> u16 {
         self.duty_c
     }
+
+    /// Gets the PWM frequency in hertz
+    #[must_use]
+    pub const fn hz(&self) -> u16 {
+        self.hz
+    }
+
+    /// Gets the timer's TOP value, which full duty corresponds to
+    #[must_use]
+    pub const fn top(&self) -> u16 {
+        self.top
+    }
+
+    /// Gets the number of bits of output resolution available at the current frequency
+    ///
+    /// Duties are scaled into `0..=top`, so once this drops below 8, neighbouring duties produce
+    /// the same output
+    #[must_use]
+    pub const fn resolution_bits(&self) -> u8 {
+        (u32::BITS - 1 - (self.top as u32 + 1).leading_zeros()) as u8
+    }
+
+    /// Set PWM duty of the given channel in the range `0..=256`, immediately and regardless of slew
+    /// rate
+    pub fn set_duty(&mut self, channel: PwmChannel, duty: u16) {
+        match channel {
+            PwmChannel::A => self.set_duty_a(duty),
+            PwmChannel::B => self.set_duty_b(duty),
+            PwmChannel::C => self.set_duty_c(duty),
+        }
+    }
+
+    /// Gets PWM duty of the given channel in the range `0..=256`, as set rather than as remapped
+    /// onto the channel's minimum duty
+    #[must_use]
+    pub const fn duty(&self, channel: PwmChannel) -> u16 {
+        match channel {
+            PwmChannel::A => self.duty_a,
+            PwmChannel::B => self.duty_b,
+            PwmChannel::C => self.duty_c,
+        }
+    }
+
+    /// Set the minimum nonzero duty of the given channel, for loads that stall below a certain duty
+    ///
+    /// Takes effect on the next duty set on the channel; see [`PWMController::set_min_duty_a`]
+    pub const fn set_min_duty(&mut self, channel: PwmChannel, min_duty: u16) {
+        match channel {
+            PwmChannel::A => self.set_min_duty_a(min_duty),
+            PwmChannel::B => self.set_min_duty_b(min_duty),
+            PwmChannel::C => self.set_min_duty_c(min_duty),
+        }
+    }
+
+    /// Set whether the given channel's output is electrically inverted, for loads that run at full
+    /// speed on zero duty
+    pub fn set_invert(&mut self, channel: PwmChannel, invert: bool) {
+        match channel {
+            PwmChannel::A => self.set_invert_a(invert),
+            PwmChannel::B => self.set_invert_b(invert),
+            PwmChannel::C => self.set_invert_c(invert),
+        }
+    }
+
+    /// Gets the duty in the range `0..=256` actually driven on the given channel, after remapping
+    /// onto its minimum duty
+    #[must_use]
+    pub const fn output_duty(&self, channel: PwmChannel) -> u16 {
+        self.remap(channel, self.duty(channel))
+    }
+
+    const fn min_duty(&self, channel: PwmChannel) -> u16 {
+        match channel {
+            PwmChannel::A => self.min_duty_a,
+            PwmChannel::B => self.min_duty_b,
+            PwmChannel::C => self.min_duty_c,
+        }
+    }
+
+    /// Drive the given channel fully on or off instead of proportionally: it switches on once a
+    /// duty above `on` is set and off once one below `off` (or 0) is set, holding its state for
+    /// anything in between so jitter around a single threshold can't chatter it, or go back to
+    /// proportional control with `None`
+    ///
+    /// The channel's minimum duty doesn't apply while switching, and the duty reported by
+    /// [`PWMController::duty`] remains the last one set. Changing the frequency with
+    /// [`PWMController::set_hz`] or [`PWMController::set_hz_seamless`] reapplies the held state
+    /// against the new TOP without re-evaluating the thresholds. Takes effect on the next duty set
+    /// on the channel
+    pub const fn set_bang_bang(&mut self, channel: PwmChannel, thresholds: Option<(u16, u16)>) {
+        self.bang_bang[channel as usize] = thresholds;
+    }
+
+    /// Update the given channel's on/off state for a newly set duty, if it is switching
+    const fn latch(&mut self, channel: PwmChannel, duty: u16) {
+        if let Some((on, off)) = self.bang_bang[channel as usize] {
+            if duty > on {
+                self.bang_on[channel as usize] = true;
+            } else if duty < off || duty == 0 {
+                self.bang_on[channel as usize] = false;
+            }
+        }
+    }
+
+    /// Remap a nonzero duty onto the range from the given channel's minimum duty to `256`, or to
+    /// fully on or off if the channel is switching
+    const fn remap(&self, channel: PwmChannel, duty: u16) -> u16 {
+        if self.bang_bang[channel as usize].is_some() {
+            return if self.bang_on[channel as usize] {
+                256
+            } else {
+                0
+            };
+        }
+        if duty == 0 {
+            return 0;
+        }
+
+        let min_duty = self.min_duty(channel);
+        min_duty + (((256 - min_duty) as u32 * duty as u32) >> 8) as u16
+    }
+
+    /// Compare value driving `duty` on the given channel at the current TOP; 0 is always exactly 0
+    const fn compare(&self, channel: PwmChannel, duty: u16) -> u16 {
+        ((self.top as u32 * self.remap(channel, duty) as u32) >> 8) as u16
+    }
+
+    /// Set the largest change in duty the given channel may make per call to
+    /// [`PWMController::slew`], smoothing commanded duties to avoid hunting; 0 disables smoothing
+    pub const fn set_slew_rate(&mut self, channel: PwmChannel, max_step: u16) {
+        self.slew[channel as usize] = max_step;
+    }
+
+    /// Command a duty in the range `0..=256` on the given channel, to be approached at the
+    /// channel's slew rate, or set immediately if it has none
+    pub fn command_duty(&mut self, channel: PwmChannel, duty: u16) {
+        if self.slew[channel as usize] == 0 {
+            self.set_duty(channel, duty);
+        } else {
+            self.commanded[channel as usize] = duty.min(256);
+        }
+    }
+
+    /// Step every channel toward its commanded duty by at most its slew rate
+    ///
+    /// Should be called at a fixed interval, which together with the slew rates sets how quickly
+    /// the outputs follow their commands. Duties are only scaled to TOP as they are written, so a
+    /// ramp carries on unaffected across a frequency change
+    pub fn slew(&mut self) {
+        for channel in [PwmChannel::A, PwmChannel::B, PwmChannel::C] {
+            let duty = self.duty(channel);
+            let target = self.commanded[channel as usize];
+            let step = self.slew[channel as usize];
+
+            let next = match target.cmp(&duty) {
+                Ordering::Greater => target.min(duty.saturating_add(step)),
+                Ordering::Less => target.max(duty.saturating_sub(step)),
+                Ordering::Equal => continue,
+            };
+
+            self.set_duty(channel, next);
+            self.commanded[channel as usize] = target;
+        }
+    }
+}
+
+/// One of the three channels of a [`PWMController`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PwmChannel {
+    /// Channel A
+    A,
+    /// Channel B
+    B,
+    /// Channel C
+    C,
+}
+
+/// [`PWMController`] with its channels named after the loads they drive
+///
+/// Only what isn't specific to a load, such as the frequency and slewing, is forwarded to the
+/// underlying controller as is; every per-channel feature is reached through a method named after
+/// its load, so a setting can't end up on the wrong channel
+pub struct ClimatePwm(PWMController);
+
+impl ClimatePwm {
+    /// Channel driving the condenser fan
+    pub const CONDENSER_FAN: PwmChannel = PwmChannel::A;
+
+    /// Channel driving the habitat fan
+    pub const HABITAT_FAN: PwmChannel = PwmChannel::B;
+
+    /// Channel driving the coolant pump
+    pub const COOLANT_PUMP: PwmChannel = PwmChannel::C;
+
+    /// Name the channels of the given controller
+    #[must_use]
+    pub const fn new(pwm: PWMController) -> Self {
+        Self(pwm)
+    }
+
+    /// Set the condenser fan duty in the range `0..=256`, immediately and regardless of slew rate
+    pub fn set_condenser_fan(&mut self, duty: u16) {
+        self.0.set_duty(Self::CONDENSER_FAN, duty);
+    }
+
+    /// Set the habitat fan duty in the range `0..=256`, immediately and regardless of slew rate
+    pub fn set_habitat_fan(&mut self, duty: u16) {
+        self.0.set_duty(Self::HABITAT_FAN, duty);
+    }
+
+    /// Set the coolant pump duty in the range `0..=256`, immediately and regardless of slew rate
+    pub fn set_coolant_pump(&mut self, duty: u16) {
+        self.0.set_duty(Self::COOLANT_PUMP, duty);
+    }
+
+    /// Gets the condenser fan duty in the range `0..=256`
+    #[must_use]
+    pub const fn condenser_fan(&self) -> u16 {
+        self.0.duty(Self::CONDENSER_FAN)
+    }
+
+    /// Gets the habitat fan duty in the range `0..=256`
+    #[must_use]
+    pub const fn habitat_fan(&self) -> u16 {
+        self.0.duty(Self::HABITAT_FAN)
+    }
+
+    /// Gets the coolant pump duty in the range `0..=256`
+    #[must_use]
+    pub const fn coolant_pump(&self) -> u16 {
+        self.0.duty(Self::COOLANT_PUMP)
+    }
+
+    /// Command a condenser fan duty in the range `0..=256`, approached at the fan's slew rate
+    pub fn command_condenser_fan(&mut self, duty: u16) {
+        self.0.command_duty(Self::CONDENSER_FAN, duty);
+    }
+
+    /// Command a coolant pump duty in the range `0..=256`, approached at the pump's slew rate
+    pub fn command_coolant_pump(&mut self, duty: u16) {
+        self.0.command_duty(Self::COOLANT_PUMP, duty);
+    }
+
+    /// Set the largest step the condenser fan duty may take per [`ClimatePwm::slew`]
+    pub const fn set_condenser_fan_slew(&mut self, max_step: u16) {
+        self.0.set_slew_rate(Self::CONDENSER_FAN, max_step);
+    }
+
+    /// Set the largest step the coolant pump duty may take per [`ClimatePwm::slew`]
+    pub const fn set_coolant_pump_slew(&mut self, max_step: u16) {
+        self.0.set_slew_rate(Self::COOLANT_PUMP, max_step);
+    }
+
+    /// Set the duty the habitat fan needs to keep turning; see [`PWMController::set_min_duty_a`]
+    pub const fn set_habitat_fan_min_duty(&mut self, min_duty: u16) {
+        self.0.set_min_duty(Self::HABITAT_FAN, min_duty);
+    }
+
+    /// Set the duty the coolant pump needs to keep turning; see [`PWMController::set_min_duty_a`]
+    pub const fn set_coolant_pump_min_duty(&mut self, min_duty: u16) {
+        self.0.set_min_duty(Self::COOLANT_PUMP, min_duty);
+    }
+
+    /// Switch the habitat fan fully on or off between thresholds instead of modulating it; see
+    /// [`PWMController::set_bang_bang`]
+    pub const fn set_habitat_fan_switching(&mut self, thresholds: Option<(u16, u16)>) {
+        self.0.set_bang_bang(Self::HABITAT_FAN, thresholds);
+    }
+
+    /// Set the duty of a channel chosen at runtime, e.g. by a manual override, in the range
+    /// `0..=256`, immediately and regardless of slew rate
+    pub fn set_duty(&mut self, channel: PwmChannel, duty: u16) {
+        self.0.set_duty(channel, duty);
+    }
+
+    /// Gets the duty in the range `0..=256` actually driven on the given channel
+    #[must_use]
+    pub const fn output_duty(&self, channel: PwmChannel) -> u16 {
+        self.0.output_duty(channel)
+    }
+
+    /// Step every channel toward its commanded duty; see [`PWMController::slew`]
+    pub fn slew(&mut self) {
+        self.0.slew();
+    }
+
+    /// Change PWM frequency; see [`PWMController::set_hz`]
+    pub fn set_hz(&mut self, hz: u16) {
+        self.0.set_hz(hz);
+    }
+
+    /// Change PWM frequency at the start of a cycle; see [`PWMController::set_hz_seamless`]
+    pub fn set_hz_seamless(&mut self, hz: u16) {
+        self.0.set_hz_seamless(hz);
+    }
+
+    /// Gets the PWM frequency in hertz
+    #[must_use]
+    pub const fn hz(&self) -> u16 {
+        self.0.hz()
+    }
+
+    /// Gets the effective duty resolution in bits; see [`PWMController::resolution_bits`]
+    #[must_use]
+    pub const fn resolution_bits(&self) -> u8 {
+        self.0.resolution_bits()
+    }
+}
+
+/// Output that can be switched on and off by the control loop
+///
+/// Implemented by every relay-like output so owners can drive them, and track their runtime,
+/// without caring how they are switched. The trait is object-safe, so outputs can also be held as
+/// `&mut dyn Switchable`
+pub trait Switchable {
+    /// Request the output on, returning whether it was switched on
+    fn activate(&mut self, now: u32) -> bool;
+
+    /// Request the output off, returning whether it was switched off
+    fn deactivate(&mut self, now: u32) -> bool;
+
+    /// Checks if the output is on, regardless of whether its state has been verified
+    fn is_active(&self) -> bool;
+
+    /// Advance any time-dependent behavior; should be called once per update
+    fn tick(&mut self, now: u32);
 }
 
 enum RelayState {
@@ -164,12 +623,17 @@ enum RelayState {
 }
 
 /// Relay state machine with error detection/correction
+///
+/// An optional lockout keeps the relay from being switched back on too soon after being switched
+/// off, e.g. to stop a compressor short-cycling against high head pressure
 pub struct Relay<PIN> {
     pin: Pin<Output, PIN>,
     state: RelayState,
     verify_off_delay: u16,
     verify_on_delay: u16,
     blip_delay: u16,
+    lockout: u16,
+    off_since: u32,
 }
 
 impl<PIN> Relay<PIN>
@@ -189,9 +653,28 @@ where
             verify_off_delay,
             verify_on_delay,
             blip_delay,
+            lockout: 0,
+            off_since: 0,
         }
     }
 
+    /// Set the minimum time in seconds the relay must stay off before it may be switched on again
+    ///
+    /// The lockout also applies from startup, as if the relay had just been switched off
+    #[must_use]
+    pub const fn with_lockout(mut self, lockout: u16) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// Checks if the relay is in the verified off state and its lockout has elapsed, i.e. whether
+    /// [`Relay::turn_on`] would succeed
+    #[must_use]
+    pub const fn can_activate(&self, now: u32) -> bool {
+        matches!(self.state, RelayState::VerifiedOff)
+            && now.wrapping_sub(self.off_since) >= self.lockout as u32 * 1000
+    }
+
     /// Checks if the relay is on, regardless of whether its state has been verified
     pub const fn is_on(&self) -> bool {
         matches!(self.state, RelayState::TurnedOn(_) | RelayState::VerifiedOn)
@@ -205,10 +688,22 @@ where
         )
     }
 
-    /// Attempts to switch the relay on only if it in the verified off state, returning whether that
-    /// succeeded
+    /// Time the relay was last switched off, if it is currently off
+    ///
+    /// A relay that has not been switched off since startup reports 0
+    #[must_use]
+    pub const fn off_since(&self) -> Option<u32> {
+        if self.is_off() {
+            Some(self.off_since)
+        } else {
+            None
+        }
+    }
+
+    /// Attempts to switch the relay on only if it in the verified off state and its lockout has
+    /// elapsed, returning whether that succeeded
     pub fn turn_on(&mut self, now: u32) -> bool {
-        if matches!(self.state, RelayState::VerifiedOff) {
+        if self.can_activate(now) {
             self.pin.set_high();
             self.state = RelayState::TurnedOn(now);
             true
@@ -223,6 +718,7 @@ where
         if matches!(self.state, RelayState::VerifiedOn) {
             self.pin.set_low();
             self.state = RelayState::TurnedOff(now);
+            self.off_since = now;
             true
         } else {
             false
@@ -269,6 +765,7 @@ where
             if now - when >= self.blip_delay as u32 * 1000 {
                 self.pin.set_low();
                 self.state = RelayState::TurnedOff(now);
+                self.off_since = now;
             }
         }
     }
@@ -281,11 +778,557 @@ where
         self.pin.set_high();
     }
 
-    /// Forces the relay into the verified off state
+    /// Forces the relay into the verified off state, starting its lockout at `now` if it was on
     ///
     /// Note: should only be used for manual relay control
-    pub fn force_off(&mut self) {
+    pub fn force_off(&mut self, now: u32) {
+        if !self.is_off() {
+            self.off_since = now;
+        }
         self.state = RelayState::VerifiedOff;
         self.pin.set_low();
     }
 }
+
+impl<PIN> Switchable for Relay<PIN>
+where
+    PIN: PinOps,
+{
+    fn activate(&mut self, now: u32) -> bool {
+        self.turn_on(now)
+    }
+
+    fn deactivate(&mut self, now: u32) -> bool {
+        self.turn_off(now)
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_on()
+    }
+
+    /// Restores the relay after a failed verification; see [`Relay::restore_when_ready`]
+    fn tick(&mut self, now: u32) {
+        self.restore_when_ready(now);
+    }
+}
+
+/// Pair of [`Relay`]s that must never be on at the same time
+///
+/// Requests are checked before anything is switched: a relay is only switched on once the other is
+/// commanded off, so at no point are both energized. If both are requested on, `a` takes priority.
+/// Verification and restoration remain the owner's responsibility via [`Interlock::a_mut`] and
+/// [`Interlock::b_mut`]
+#[must_use]
+pub struct Interlock<A, B> {
+    a: Relay<A>,
+    b: Relay<B>,
+}
+
+impl<A, B> Interlock<A, B>
+where
+    A: PinOps,
+    B: PinOps,
+{
+    /// Bind two relays together under mutual exclusion
+    pub const fn new(a: Relay<A>, b: Relay<B>) -> Self {
+        Self { a, b }
+    }
+
+    /// Request the state of both relays
+    ///
+    /// Relays that should be off are switched off first; a relay that should be on is only switched
+    /// on if the other is off, so a changeover may take several calls to complete
+    pub fn set(&mut self, now: u32, a_on: bool, b_on: bool) {
+        let b_on = b_on && !a_on;
+
+        if !a_on && self.a.is_on() {
+            self.a.turn_off(now);
+        }
+        if !b_on && self.b.is_on() {
+            self.b.turn_off(now);
+        }
+
+        if a_on && self.b.is_off() && self.a.is_off() {
+            self.a.turn_on(now);
+        } else if b_on && self.a.is_off() && self.b.is_off() {
+            self.b.turn_on(now);
+        }
+    }
+
+    /// Access relay `a`
+    ///
+    /// Note: forcing the relay bypasses the interlock
+    pub const fn a_mut(&mut self) -> &mut Relay<A> {
+        &mut self.a
+    }
+
+    /// Access relay `b`
+    ///
+    /// Note: forcing the relay bypasses the interlock
+    pub const fn b_mut(&mut self) -> &mut Relay<B> {
+        &mut self.b
+    }
+}
+
+/// Time-proportioning ("slow PWM") control of a [`Relay`]
+///
+/// Within each window, the relay is switched on for a fraction of the window equal to the duty,
+/// then off for the remainder. Pulses or gaps shorter than the minimum on-time are skipped entirely
+/// to avoid chattering the contacts
+///
+/// Note: the relay only switches when its state machine allows it, so the window should be
+/// comfortably longer than the relay's verification delays; verification and restoration remain
+/// the owner's responsibility via [`SlowPwmRelay::relay_mut`]
+#[must_use]
+pub struct SlowPwmRelay<PIN> {
+    relay: Relay<PIN>,
+    window_ms: u16,
+    min_on_ms: u16,
+    on_ms: u16,
+    window_start: u32,
+}
+
+impl<PIN> SlowPwmRelay<PIN>
+where
+    PIN: PinOps,
+{
+    /// Wrap a relay with the given window length and minimum on-time, starting at zero duty
+    pub const fn new(relay: Relay<PIN>, window_ms: u16, min_on_ms: u16) -> Self {
+        Self {
+            relay,
+            window_ms,
+            min_on_ms,
+            on_ms: 0,
+            window_start: 0,
+        }
+    }
+
+    /// Set the fraction of each window for which the relay should be on; clamped to 0.0-1.0
+    ///
+    /// Takes effect from the current window
+    pub fn set_duty(&mut self, duty: f32) {
+        let on_ms = (duty.clamp(0.0, 1.0) * u16_to_f32(self.window_ms)) as u16;
+        self.on_ms = if on_ms < self.min_on_ms {
+            0
+        } else if self.window_ms - on_ms < self.min_on_ms {
+            self.window_ms
+        } else {
+            on_ms
+        };
+    }
+
+    /// Switch the relay as needed to follow the duty
+    pub fn tick(&mut self, now: u32) {
+        if now.wrapping_sub(self.window_start) >= self.window_ms as u32 {
+            self.window_start = now;
+        }
+
+        if now.wrapping_sub(self.window_start) < self.on_ms as u32 {
+            if self.relay.is_off() {
+                self.relay.turn_on(now);
+            }
+        } else if self.relay.is_on() {
+            self.relay.turn_off(now);
+        }
+    }
+
+    /// Access the underlying relay
+    pub const fn relay_mut(&mut self) -> &mut Relay<PIN> {
+        &mut self.relay
+    }
+}
+
+impl<PIN> Switchable for SlowPwmRelay<PIN>
+where
+    PIN: PinOps,
+{
+    /// Runs the relay at full duty
+    fn activate(&mut self, now: u32) -> bool {
+        self.set_duty(1.0);
+        Self::tick(self, now);
+        self.relay.is_on()
+    }
+
+    /// Runs the relay at zero duty
+    fn deactivate(&mut self, now: u32) -> bool {
+        self.set_duty(0.0);
+        Self::tick(self, now);
+        self.relay.is_off()
+    }
+
+    fn is_active(&self) -> bool {
+        self.relay.is_on()
+    }
+
+    fn tick(&mut self, now: u32) {
+        Self::tick(self, now);
+    }
+}
+
+/// Compressor output stage, either a contactor or a variable-capacity drive
+///
+/// A soft-starter/inverter accepting a PWM (or, once filtered, 0-10 V) capacity command can be
+/// driven from a spare [`PWMController`] channel instead of cycling a contactor. Both variants
+/// expose the same interface, so the control loop need not know which is fitted; the capacity
+/// command is simply ignored by the relay
+#[must_use]
+pub enum CompressorDrive<PIN> {
+    /// On/off contactor with verification
+    Relay(Relay<PIN>),
+    /// Capacity command on a PWM channel
+    Modulating {
+        /// Channel carrying the capacity command
+        channel: PwmChannel,
+        /// Capacity in the range `0..=256` to command while running
+        capacity: u16,
+        /// Whether the drive is currently commanded to run
+        running: bool,
+        /// Time the drive was last stopped
+        stopped_at: u32,
+    },
+}
+
+impl<PIN> CompressorDrive<PIN>
+where
+    PIN: PinOps,
+{
+    /// Drive a variable-capacity compressor from the given channel, initially stopped and set to
+    /// full capacity
+    pub const fn modulating(channel: PwmChannel) -> Self {
+        Self::Modulating {
+            channel,
+            capacity: 256,
+            running: false,
+            stopped_at: 0,
+        }
+    }
+
+    /// Checks if the compressor could be started right now; see [`Relay::can_activate`]
+    #[must_use]
+    pub const fn can_activate(&self, now: u32) -> bool {
+        match self {
+            Self::Relay(relay) => relay.can_activate(now),
+            Self::Modulating { running, .. } => !*running,
+        }
+    }
+
+    /// Checks if the compressor is commanded to run
+    #[must_use]
+    pub const fn is_on(&self) -> bool {
+        match self {
+            Self::Relay(relay) => relay.is_on(),
+            Self::Modulating { running, .. } => *running,
+        }
+    }
+
+    /// Duty in the range `0..=256` the compressor is run at; a relay drive runs flat out when on
+    #[must_use]
+    pub const fn duty(&self) -> u16 {
+        match self {
+            Self::Relay(relay) => relay.is_on() as u16 * 256,
+            Self::Modulating {
+                capacity, running, ..
+            } => *running as u16 * *capacity,
+        }
+    }
+
+    /// Time the compressor was last stopped, if it is currently stopped; see [`Relay::off_since`]
+    #[must_use]
+    pub const fn off_since(&self) -> Option<u32> {
+        match self {
+            Self::Relay(relay) => relay.off_since(),
+            Self::Modulating { running: true, .. } => None,
+            Self::Modulating { stopped_at, .. } => Some(*stopped_at),
+        }
+    }
+
+    /// Attempts to start the compressor, returning whether that succeeded
+    ///
+    /// See [`Relay::turn_on`] for the restrictions on a relay drive
+    pub fn turn_on(&mut self, now: u32, pwm: &mut ClimatePwm) -> bool {
+        match self {
+            Self::Relay(relay) => relay.turn_on(now),
+            Self::Modulating {
+                channel,
+                capacity,
+                running,
+                ..
+            } => {
+                if *running {
+                    false
+                } else {
+                    pwm.set_duty(*channel, *capacity);
+                    *running = true;
+                    true
+                }
+            }
+        }
+    }
+
+    /// Attempts to stop the compressor, returning whether that succeeded
+    ///
+    /// See [`Relay::turn_off`] for the restrictions on a relay drive
+    pub fn turn_off(&mut self, now: u32, pwm: &mut ClimatePwm) -> bool {
+        match self {
+            Self::Relay(relay) => relay.turn_off(now),
+            Self::Modulating {
+                channel,
+                running,
+                stopped_at,
+                ..
+            } => {
+                if *running {
+                    pwm.set_duty(*channel, 0);
+                    *running = false;
+                    *stopped_at = now;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Set the capacity in the range `0..=256` to command while running; no effect on a relay drive
+    pub fn set_capacity(&mut self, pwm: &mut ClimatePwm, new_capacity: u16) {
+        if let Self::Modulating {
+            channel,
+            capacity,
+            running,
+            ..
+        } = self
+        {
+            *capacity = new_capacity.min(256);
+            if *running {
+                pwm.set_duty(*channel, *capacity);
+            }
+        }
+    }
+
+    /// See [`Relay::verify_when_ready`]; no effect on a modulating drive
+    pub fn verify_when_ready<F, G>(&mut self, now: u32, verify_on: F, verify_off: G)
+    where
+        F: FnOnce() -> bool,
+        G: FnOnce() -> bool,
+    {
+        if let Self::Relay(relay) = self {
+            relay.verify_when_ready(now, verify_on, verify_off);
+        }
+    }
+
+    /// See [`Relay::restore_when_ready`]; no effect on a modulating drive
+    pub fn restore_when_ready(&mut self, now: u32) {
+        if let Self::Relay(relay) = self {
+            relay.restore_when_ready(now);
+        }
+    }
+
+    /// Forces the compressor on
+    ///
+    /// Note: should only be used for manual control
+    pub fn force_on(&mut self, pwm: &mut ClimatePwm) {
+        match self {
+            Self::Relay(relay) => relay.force_on(),
+            Self::Modulating {
+                channel,
+                capacity,
+                running,
+                ..
+            } => {
+                pwm.set_duty(*channel, *capacity);
+                *running = true;
+            }
+        }
+    }
+
+    /// Forces the compressor off, starting its lockout at `now` if it was running
+    ///
+    /// Note: should only be used for manual control
+    pub fn force_off(&mut self, now: u32, pwm: &mut ClimatePwm) {
+        match self {
+            Self::Relay(relay) => relay.force_off(now),
+            Self::Modulating {
+                channel,
+                running,
+                stopped_at,
+                ..
+            } => {
+                pwm.set_duty(*channel, 0);
+                if *running {
+                    *stopped_at = now;
+                }
+                *running = false;
+            }
+        }
+    }
+}
+
+/// On-fraction of an output over a rolling window, e.g. to gauge how hard the compressor is working
+///
+/// The window is divided into `N` buckets, each accumulating the milliseconds the output was on
+/// during its slice of the window; the oldest bucket is dropped as each new one starts. Time before
+/// startup counts as off
+#[must_use]
+pub struct DutyWindow<const N: usize> {
+    buckets: [u16; N],
+    bucket_ms: u16,
+    idx: usize,
+    bucket_start: u32,
+    last: u32,
+}
+
+impl<const N: usize> DutyWindow<N> {
+    /// Create an empty window of `N` buckets of `bucket_ms` milliseconds each
+    pub const fn new(bucket_ms: u16) -> Self {
+        Self {
+            buckets: [0; N],
+            bucket_ms,
+            idx: 0,
+            bucket_start: 0,
+            last: 0,
+        }
+    }
+
+    /// Account for the time since the last update, during which the output was in state `on`
+    pub const fn update(&mut self, now: u32, on: bool) {
+        while now.wrapping_sub(self.bucket_start) >= self.bucket_ms as u32 {
+            let end = self.bucket_start.wrapping_add(self.bucket_ms as u32);
+            if on {
+                self.credit(end);
+            }
+            self.last = end;
+            self.bucket_start = end;
+            self.idx = (self.idx + 1) % N;
+            self.buckets[self.idx] = 0;
+        }
+
+        if on {
+            self.credit(now);
+        }
+        self.last = now;
+    }
+
+    const fn credit(&mut self, until: u32) {
+        let on_ms = until.wrapping_sub(self.last) as u16;
+        self.buckets[self.idx] = self.buckets[self.idx].saturating_add(on_ms);
+    }
+
+    /// Percentage of the window for which the output was on
+    #[must_use]
+    pub const fn percent(&self) -> u8 {
+        let mut on_ms = 0;
+        let mut i = 0;
+        while i < N {
+            on_ms += self.buckets[i] as u32;
+            i += 1;
+        }
+        let window_ms =
+            (N as u32 - 1) * self.bucket_ms as u32 + self.last.wrapping_sub(self.bucket_start);
+
+        if window_ms == 0 {
+            0
+        } else {
+            (on_ms * 100 / window_ms) as u8
+        }
+    }
+}
+
+/// Watt-milliseconds in a watt-hour, scaled by the full duty of 256
+const DUTY_WATT_MS_PER_WH: u32 = 3_600_000 * 256;
+
+/// Longest interval in milliseconds integrated in one step, for which even 65535 W at full duty
+/// fits in a [`u32`]
+const MAX_STEP_MS: u32 = 255;
+
+/// Estimated energy used by a set of `N` loads, from the nominal power of each and the duty it is
+/// driven at
+///
+/// Power is taken to scale linearly with duty, which is only roughly true of fans and pumps, so
+/// the estimate is for comparing control strategies rather than a substitute for a meter
+#[must_use]
+pub struct EnergyMeter<const N: usize> {
+    wh: [u32; N],
+    duty_watt_ms: [u32; N],
+    last: Option<u32>,
+}
+
+impl<const N: usize> EnergyMeter<N> {
+    /// Create a meter with nothing used yet
+    pub const fn new() -> Self {
+        Self {
+            wh: [0; N],
+            duty_watt_ms: [0; N],
+            last: None,
+        }
+    }
+
+    /// Account for the time since the last update, during which each load was driven at the given
+    /// duty, in the range `0..=256`, of its nominal power in watts
+    ///
+    /// Should be called at a fixed interval of around a second; each call integrates the interval
+    /// in steps of up to 255 ms
+    pub const fn update(&mut self, now: u32, loads: [(u16, u16); N]) {
+        let mut elapsed = match self.last {
+            Some(last) => now.wrapping_sub(last),
+            None => 0,
+        };
+        self.last = Some(now);
+
+        while elapsed > 0 {
+            let step = if elapsed > MAX_STEP_MS {
+                MAX_STEP_MS
+            } else {
+                elapsed
+            };
+            elapsed -= step;
+
+            let mut i = 0;
+            while i < N {
+                let (watts, duty) = loads[i];
+                let duty = if duty > 256 { 256 } else { duty as u32 };
+                // Only scaled down by the full duty once integrated, so that a low duty or a short
+                // interval isn't truncated away
+                let used = watts as u32 * duty * step;
+                let mut carried = self.duty_watt_ms[i] + used % DUTY_WATT_MS_PER_WH;
+                let mut wh = used / DUTY_WATT_MS_PER_WH;
+                if carried >= DUTY_WATT_MS_PER_WH {
+                    carried -= DUTY_WATT_MS_PER_WH;
+                    wh += 1;
+                }
+                self.wh[i] = self.wh[i].saturating_add(wh);
+                self.duty_watt_ms[i] = carried;
+                i += 1;
+            }
+        }
+    }
+
+    /// Whole watt-hours used by load `n` since the meter was created or last reset
+    #[must_use]
+    pub const fn wh(&self, n: usize) -> u32 {
+        self.wh[n]
+    }
+
+    /// Whole watt-hours used by every load together
+    #[must_use]
+    pub const fn total_wh(&self) -> u32 {
+        let mut total = 0u32;
+        let mut i = 0;
+        while i < N {
+            total = total.saturating_add(self.wh[i]);
+            i += 1;
+        }
+        total
+    }
+
+    /// Forget the energy used so far
+    pub const fn reset(&mut self) {
+        self.wh = [0; N];
+        self.duty_watt_ms = [0; N];
+    }
+}
+
+impl<const N: usize> Default for EnergyMeter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,23 +1,71 @@
 //! Display subsystem
 
 use arduino_hal::{
-    hal::port::{PB0, PB1, PB2, PB3, PD2, PD3},
+    hal::port::{PB0, PB1, PB2, PB3, PD2, PD3, PF7},
     port::{
         mode::{Floating, Input, Output},
         Pin,
     },
 };
 
+/// Number of character columns on the display
+///
+/// Supported layouts are 16x2, 20x2, 16x4 and 20x4; note that the pages built in `main.rs` are laid
+/// out for 20x4 and will fail to compile against a smaller page
+pub const COLS: usize = 20;
+
+/// Number of character rows on the display
+pub const ROWS: usize = 4;
+
+/// Number of characters in a complete page
+pub const PAGE_SIZE: usize = COLS * ROWS;
+
+/// Microseconds the controller takes to execute a character write or any command other than
+/// clear and home; the HD44780 needs 37us (41us for writes) at its nominal 270 kHz, and clones
+/// running at the low end of their oscillator tolerance (~190 kHz) need ~52us, so this leaves a
+/// margin over the slowest
+const EXEC_US: u32 = 60;
+
+/// DDRAM address of the start of each row
+const ROW_OFFSETS: [u8; 4] = [0x00, 0x40, COLS as u8, 0x40 + COLS as u8];
+
+const _: () = assert!(
+    (COLS == 16 || COLS == 20) && (ROWS == 2 || ROWS == 4),
+    "unsupported display layout"
+);
+
+/// Character code of the custom up arrow glyph
+pub const GLYPH_UP: u8 = 0;
+
+/// Character code of the custom down arrow glyph
+pub const GLYPH_DOWN: u8 = 1;
+
+/// Custom glyphs loaded into CGRAM by [`Display::init`], indexed by character code
+const GLYPHS: [[u8; 8]; 2] = [
+    [
+        0b00100, 0b01110, 0b10101, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000,
+    ],
+    [
+        0b00100, 0b00100, 0b00100, 0b00100, 0b10101, 0b01110, 0b00100, 0b00000,
+    ],
+];
+
+/// Boot message shown by [`Display::splash`]
+const SPLASH_NAME: &[u8; 12] = b"formiclimate";
+const SPLASH_VERSION: &[u8] = concat!("v", env!("CARGO_PKG_VERSION")).as_bytes();
+
 /// A complete page ready to be sent to the display
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 #[must_use]
 #[repr(C)]
 pub struct PageData {
-    data: [u8; 80],
+    data: [u8; PAGE_SIZE],
 }
 
 impl PageData {
-    const BLANK: Self = Self { data: [b' '; 80] };
+    const BLANK: Self = Self {
+        data: [b' '; PAGE_SIZE],
+    };
 
     /// Create a new blank page (all spaces)
     pub const fn blank() -> Self {
@@ -25,7 +73,7 @@ impl PageData {
     }
 
     /// Create a new page from the given character data
-    pub const fn new(data: [u8; 80]) -> Self {
+    pub const fn new(data: [u8; PAGE_SIZE]) -> Self {
         Self { data }
     }
 
@@ -37,33 +85,15 @@ impl PageData {
     /// Position after padding the current line to its end (no-op if already at a line boundary)
     #[must_use]
     pub const fn end_line_pos(pos: usize) -> usize {
-        if pos <= 20 {
-            20
-        } else if pos <= 40 {
-            40
-        } else if pos <= 60 {
-            60
-        } else if pos <= 80 {
-            80
-        } else {
-            panic!("invalid positon")
-        }
+        assert!(pos <= PAGE_SIZE, "invalid position");
+        pos.div_ceil(COLS) * COLS
     }
 
     /// Position at the start of the next line
     #[must_use]
     pub const fn next_line_pos(pos: usize) -> usize {
-        if pos < 20 {
-            20
-        } else if pos < 40 {
-            40
-        } else if pos < 60 {
-            60
-        } else if pos < 80 {
-            80
-        } else {
-            panic!("invalid positon")
-        }
+        assert!(pos < PAGE_SIZE, "invalid position");
+        (pos / COLS + 1) * COLS
     }
 
     /// Write a single byte at `pos`
@@ -80,15 +110,25 @@ impl PageData {
         }
     }
 
+    /// Read the underlying character data
+    #[must_use]
+    pub const fn data(&self) -> &[u8; PAGE_SIZE] {
+        &self.data
+    }
+
     /// Return the underlying character data
     #[must_use]
-    pub const fn into_data(self) -> [u8; 80] {
+    pub const fn into_data(self) -> [u8; PAGE_SIZE] {
         self.data
     }
 }
 
 /// Climate controller display subsystem
 ///
+/// The LCD's RW line isn't wired, so there is no way to detect a missing or miswired module. A
+/// display marked absent with [`Display::with_present`] skips every LCD transaction (and its
+/// delays), while pages are still generated and swapped as usual
+///
 /// Note: optimized for binary size at the cost of generic utility
 #[must_use]
 pub struct Display {
@@ -99,9 +139,13 @@ pub struct Display {
     d6: Pin<Output, PB2>,
     d7: Pin<Output, PB3>,
 
+    backlight: Pin<Output, PF7>,
+
     page_a: PageData,
     page_b: PageData,
     which: bool,
+
+    present: bool,
 }
 
 impl Display {
@@ -114,6 +158,7 @@ impl Display {
         pb1: Pin<Input<Floating>, PB1>,
         pb2: Pin<Input<Floating>, PB2>,
         pb3: Pin<Input<Floating>, PB3>,
+        pf7: Pin<Input<Floating>, PF7>,
     ) -> Self {
         Self {
             rs: pd2.into_output(),
@@ -123,21 +168,93 @@ impl Display {
             d6: pb2.into_output(),
             d7: pb3.into_output(),
 
+            backlight: pf7.into_output_high(),
+
             page_a: PageData::BLANK,
             page_b: PageData::BLANK,
             which: false,
+
+            present: true,
         }
     }
 
+    /// Set whether the LCD is fitted; if not, nothing is ever sent to it
+    pub const fn with_present(mut self, present: bool) -> Self {
+        self.present = present;
+        self
+    }
+
+    /// Checks if the LCD is marked as fitted
+    #[must_use]
+    pub const fn is_present(&self) -> bool {
+        self.present
+    }
+
     /// Initialize the display
     pub fn init(&mut self) {
+        if !self.present {
+            return;
+        }
         self.set_func(0x08); // 4-bit bus; two lines; 5x8 char size
         self.set_ctrl(0x04); // Display on; cursor/blink off
         self.set_mode(0x02); // Left-to-right layout; no display shift
+        for (code, glyph) in GLYPHS.into_iter().enumerate() {
+            self.set_glyph(code as u8, glyph);
+        }
         self.clear();
         self.home();
     }
 
+    /// Define the custom glyph shown for character code `code` (0-7), one byte per pixel row with
+    /// the low 5 bits used
+    ///
+    /// Note: leaves the address counter in CGRAM, so the position must be set before writing text
+    pub fn set_glyph(&mut self, code: u8, rows: [u8; 8]) {
+        if !self.present {
+            return;
+        }
+        self.command(0x40 | ((code & 0x7) << 3));
+        arduino_hal::delay_us(EXEC_US);
+        self.write_all(&rows);
+    }
+
+    /// Switch the backlight on or off
+    pub fn set_backlight(&mut self, on: bool) {
+        if on {
+            self.backlight.set_high();
+        } else {
+            self.backlight.set_low();
+        }
+    }
+
+    /// Checks if the backlight is on
+    #[must_use]
+    pub fn is_backlight_on(&self) -> bool {
+        self.backlight.is_set_high()
+    }
+
+    /// Show the firmware name and version for one second, then clear the display
+    ///
+    /// Must be called after [`Display::init`] and before the first [`Display::swap`]
+    pub fn splash(&mut self) {
+        if !self.present {
+            return;
+        }
+        self.write_at(
+            ((COLS - SPLASH_NAME.len()) / 2) as u8,
+            (ROWS / 2 - 1) as u8,
+            SPLASH_NAME,
+        );
+        self.write_at(
+            ((COLS - SPLASH_VERSION.len()) / 2) as u8,
+            (ROWS / 2) as u8,
+            SPLASH_VERSION,
+        );
+
+        arduino_hal::delay_ms(1000);
+        self.clear();
+    }
+
     fn clear(&mut self) {
         self.command(0x01);
         arduino_hal::delay_us(3000);
@@ -150,23 +267,25 @@ impl Display {
 
     fn set_mode(&mut self, mode: u8) {
         self.command(0x04 | mode);
-        arduino_hal::delay_us(100);
+        arduino_hal::delay_us(EXEC_US);
     }
 
     fn set_ctrl(&mut self, ctrl: u8) {
         self.command(0x08 | ctrl);
-        arduino_hal::delay_us(100);
+        arduino_hal::delay_us(EXEC_US);
     }
 
     fn set_func(&mut self, func: u8) {
         self.command(0x20 | func);
-        arduino_hal::delay_us(100);
+        arduino_hal::delay_us(EXEC_US);
     }
 
+    /// Move the cursor; columns past the end of the line are clamped to the last column, as the
+    /// address would otherwise run on into another row
     fn set_pos(&mut self, col: u8, row: u8) {
-        const OFFSETS: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
-        self.command(0x80 | (col + OFFSETS[(row & 0x3) as usize]));
-        arduino_hal::delay_us(100);
+        let col = col.min(COLS as u8 - 1);
+        self.command(0x80 | (col + ROW_OFFSETS[(row & 0x3) as usize]));
+        arduino_hal::delay_us(EXEC_US);
     }
 
     fn command(&mut self, cmd: u8) {
@@ -175,7 +294,23 @@ impl Display {
 
     fn write(&mut self, value: u8) {
         self.send8(value, true);
-        arduino_hal::delay_us(100);
+        arduino_hal::delay_us(EXEC_US);
+    }
+
+    /// Write consecutive characters, relying on the address counter auto-incrementing so the
+    /// position only needs setting once
+    fn write_all(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write(byte);
+        }
+    }
+
+    /// Write text starting at the given position, cut off at the end of the line rather than
+    /// letting the address counter carry it into another row
+    fn write_at(&mut self, col: u8, row: u8, bytes: &[u8]) {
+        let room = COLS.saturating_sub(col as usize);
+        self.set_pos(col, row);
+        self.write_all(&bytes[..bytes.len().min(room)]);
     }
 
     #[inline(never)]
@@ -235,6 +370,18 @@ impl Display {
         }
     }
 
+    /// Returns the page currently on display
+    pub const fn current_page(&self) -> &PageData {
+        self.front()
+    }
+
+    /// Checks if the given page is exactly what is currently on display, in which case presenting
+    /// it again would write nothing
+    #[must_use]
+    pub fn is_showing(&self, page: &PageData) -> bool {
+        self.front() == page
+    }
+
     /// Returns a mutable reference to the page not currently on display
     pub const fn back_mut(&mut self) -> &mut PageData {
         if self.which {
@@ -249,42 +396,44 @@ impl Display {
     ///
     /// # Performance
     /// Execution time is variable based on how much of the new page is different from the last and
-    /// how the differences are arranged. Roughly speaking, this function takes
-    /// `([characters changed] + [runs of unchanged characters]) * 100`us
+    /// how the differences are arranged. Each run of changed characters is streamed after setting
+    /// the position once, so roughly speaking this function takes
+    /// `([characters changed] + [runs of changed characters]) * 60`us
     ///
-    /// At worst, this will take ~8-9ms in either of two cases:
+    /// At worst, this will take ~5-6ms in either of two cases:
     /// - if every single character in the new page is different from the last (80 new characters)
-    /// - if every other character is different (40 new characters with 40 unchanged runs between)
+    /// - if every other character is different (40 new characters in 40 separate runs)
+    ///
+    /// (figures for a 20x4 display)
     ///
-    /// Any other situation will take less time, down to ~400us with a completely identical page
+    /// Any other situation will take less time, down to next to nothing with an identical page
     pub fn swap(&mut self) {
-        let mut i = 0;
-        let mut col = 0;
-        let mut row = 0;
-
-        let mut skip = true;
-
         self.which = !self.which;
 
-        while i < 80 {
-            let byte = self.front().data[i];
-            if byte == self.back().data[i] {
-                skip = true;
-            } else {
-                if skip {
-                    self.set_pos(col, row);
-                    skip = false;
-                }
-                self.write(byte);
-            }
-
-            i += 1;
-            col += 1;
+        if !self.present {
+            return;
+        }
 
-            if col == 20 {
-                col = 0;
-                row += 1;
-                skip = true;
+        for row in 0..ROWS {
+            // Gather each run of changed characters into a line buffer, then stream it in one go
+            // from a single position
+            let mut run = [0; COLS];
+            let mut start = 0;
+            let mut len = 0;
+
+            for col in 0..=COLS {
+                let i = row * COLS + col;
+                if col < COLS && self.front().data[i] != self.back().data[i] {
+                    if len == 0 {
+                        start = col;
+                    }
+                    run[len] = self.front().data[i];
+                    len += 1;
+                } else if len > 0 {
+                    self.set_pos(start as u8, row as u8);
+                    self.write_all(&run[..len]);
+                    len = 0;
+                }
             }
         }
     }
@@ -302,8 +451,13 @@ impl Display {
 /// - `write b"..."`: write a fixed-length byte string literal
 /// - `write N <expr>`: write the first `N` characters of any `&[u8]` expression
 /// - `decimal <expr>`: render an `f32` in 7 characters (eg. `-999.99`)
+/// - `decimal <expr>, <quantum>`: as above, rounded to a multiple of `quantum` hundredths (`u8`)
 /// - `uint <expr>`: render a `u16` in 5 characters (eg. `65535`)
 /// - `sint <expr>`: render an `i16` in 6 characters (eg. `-32768`)
+/// - `ulong <expr>`: render a `u32` in 10 characters (eg. `4294967295`)
+/// - `slong <expr>`: render an `i32` in 11 characters (eg. `-2147483648`)
+/// - `duration <expr>`: render a `u32` count of seconds in 10 characters (eg. `999d 23:59`)
+/// - `... left <expr>`: any of the integer commands above, left-justified instead (eg. `uint left`)
 /// - `byte <expr>`: write a single character
 /// - `byte b'' if <expr>`: write the given byte only if `expr` is `true`, otherwise leave blank
 /// - `hexit2 <expr>`: render a `u8` as two hexadecimal characters (also works for BCD values)
@@ -322,12 +476,12 @@ macro_rules! page {
     // Command parsing
     (@s $d:ident [$pe:expr]) => {};
     (@s $d:ident [$pe:expr] write $bytes:literal; $($r:tt)*) => {
-        const { assert!($pe + $bytes.len() <= 80usize, "page overflow") };
+        const { assert!($pe + $bytes.len() <= $crate::display::PAGE_SIZE, "page overflow") };
         $d.write_bytes($pe, $bytes, $bytes.len());
         $crate::page!(@s $d [$pe + $bytes.len()] $($r)*);
     };
     (@s $d:ident [$pe:expr] write 2 $bytes:expr; $($r:tt)*) => {
-        const { assert!($pe + 2 <= 80usize, "page overflow") };
+        const { assert!($pe + 2 <= $crate::display::PAGE_SIZE, "page overflow") };
         {
             let [__v0, __v1] = *$bytes;
             $d.write_byte($pe, __v0);
@@ -336,7 +490,7 @@ macro_rules! page {
         $crate::page!(@s $d [$pe + 2] $($r)*);
     };
     (@s $d:ident [$pe:expr] write 3 $bytes:expr; $($r:tt)*) => {
-        const { assert!($pe + 3 <= 80usize, "page overflow") };
+        const { assert!($pe + 3 <= $crate::display::PAGE_SIZE, "page overflow") };
         {
             let [__v0, __v1, __v2] = *$bytes;
             $d.write_byte($pe, __v0);
@@ -346,36 +500,70 @@ macro_rules! page {
         $crate::page!(@s $d [$pe + 3] $($r)*);
     };
     (@s $d:ident [$pe:expr] write $n:literal $bytes:expr; $($r:tt)*) => {
-        const { assert!($pe + $n <= 80usize, "page overflow") };
+        const { assert!($pe + $n <= $crate::display::PAGE_SIZE, "page overflow") };
         $d.write_bytes($pe, $bytes, $n);
         $crate::page!(@s $d [$pe + $n] $($r)*);
     };
+    (@s $d:ident [$pe:expr] decimal $v:expr, $q:expr; $($r:tt)*) => {
+        $crate::page!(
+            @s $d [$pe] write 7 &$crate::utils::f32_to_bytes_quantized($v, $q); $($r)*
+        );
+    };
     (@s $d:ident [$pe:expr] decimal $v:expr; $($r:tt)*) => {
         $crate::page!(@s $d [$pe] write 7 &$crate::utils::f32_to_bytes($v); $($r)*);
     };
+    (@s $d:ident [$pe:expr] uint left $v:expr; $($r:tt)*) => {
+        $crate::page!(
+            @s $d [$pe] write 5 &$crate::utils::left_justify($crate::utils::u16_to_bytes($v)); $($r)*
+        );
+    };
+    (@s $d:ident [$pe:expr] sint left $v:expr; $($r:tt)*) => {
+        $crate::page!(
+            @s $d [$pe] write 6 &$crate::utils::left_justify($crate::utils::i16_to_bytes($v)); $($r)*
+        );
+    };
+    (@s $d:ident [$pe:expr] ulong left $v:expr; $($r:tt)*) => {
+        $crate::page!(
+            @s $d [$pe] write 10 &$crate::utils::left_justify($crate::utils::u32_to_bytes($v)); $($r)*
+        );
+    };
+    (@s $d:ident [$pe:expr] slong left $v:expr; $($r:tt)*) => {
+        $crate::page!(
+            @s $d [$pe] write 11 &$crate::utils::left_justify($crate::utils::i32_to_bytes($v)); $($r)*
+        );
+    };
     (@s $d:ident [$pe:expr] uint $v:expr; $($r:tt)*) => {
         $crate::page!(@s $d [$pe] write 5 &$crate::utils::u16_to_bytes($v); $($r)*);
     };
     (@s $d:ident [$pe:expr] sint $v:expr; $($r:tt)*) => {
         $crate::page!(@s $d [$pe] write 6 &$crate::utils::i16_to_bytes($v); $($r)*);
     };
+    (@s $d:ident [$pe:expr] ulong $v:expr; $($r:tt)*) => {
+        $crate::page!(@s $d [$pe] write 10 &$crate::utils::u32_to_bytes($v); $($r)*);
+    };
+    (@s $d:ident [$pe:expr] slong $v:expr; $($r:tt)*) => {
+        $crate::page!(@s $d [$pe] write 11 &$crate::utils::i32_to_bytes($v); $($r)*);
+    };
+    (@s $d:ident [$pe:expr] duration $v:expr; $($r:tt)*) => {
+        $crate::page!(@s $d [$pe] write 10 &$crate::utils::duration_to_bytes($v); $($r)*);
+    };
     (@s $d:ident [$pe:expr] byte b' '; $($r:tt)*) => {
         $crate::page!(@s $d [$pe] skip 1; $($r)*);
     };
     (@s $d:ident [$pe:expr] byte $b:literal if $cond:expr; $($r:tt)*) => {
-        const { assert!($pe < 80usize, "page overflow") };
+        const { assert!($pe < $crate::display::PAGE_SIZE, "page overflow") };
         if $cond {
             $d.write_byte($pe, $b);
         }
         $crate::page!(@s $d [$pe + 1] $($r)*);
     };
     (@s $d:ident [$pe:expr] byte $b:expr; $($r:tt)*) => {
-        const { assert!($pe < 80usize, "page overflow") };
+        const { assert!($pe < $crate::display::PAGE_SIZE, "page overflow") };
         $d.write_byte($pe, $b);
         $crate::page!(@s $d [$pe + 1] $($r)*);
     };
     (@s $d:ident [$pe:expr] hexit2 $v:expr; $($r:tt)*) => {
-        const { assert!($pe + 2 <= 80usize, "page overflow") };
+        const { assert!($pe + 2 <= $crate::display::PAGE_SIZE, "page overflow") };
         {
             let __v = $v;
             $d.write_byte($pe, $crate::utils::hexit(__v >> 4));
@@ -384,28 +572,28 @@ macro_rules! page {
         $crate::page!(@s $d [$pe + 2] $($r)*);
     };
     (@s $d:ident [$pe:expr] skip 1; $($r:tt)*) => {
-        const { assert!($pe < 80usize, "page overflow") };
+        const { assert!($pe < $crate::display::PAGE_SIZE, "page overflow") };
         $crate::page!(@s $d [$pe + 1] $($r)*);
     };
     (@s $d:ident [$pe:expr] skip $n:literal; $($r:tt)*) => {
-        const { assert!($pe + $n <= 80usize, "page overflow") };
+        const { assert!($pe + $n <= $crate::display::PAGE_SIZE, "page overflow") };
         $crate::page!(@s $d [$pe + $n] $($r)*);
     };
     (@s $d:ident [$pe:expr] end_line; $($r:tt)*) => {
         $crate::page!(@s $d [$crate::display::PageData::end_line_pos($pe)] $($r)*);
     };
     (@s $d:ident [$pe:expr] next_line; $($r:tt)*) => {
-        const { assert!($pe < 80usize, "past last line") };
+        const { assert!($pe < $crate::display::PAGE_SIZE, "past last line") };
         $crate::page!(@s $d [$crate::display::PageData::next_line_pos($pe)] $($r)*);
     };
     (@s $d:ident [$pe:expr] end_page; $($r:tt)*) => {
-        $crate::page!(@s $d [80] $($r)*);
+        $crate::page!(@s $d [$crate::display::PAGE_SIZE] $($r)*);
     };
     (@s $d:ident [$pe:expr] if $name:ident ($($cond:tt)*) { $($if_t:tt)* } else { $($if_f:tt)* } $($r:tt)*) => {{
         const $name: usize = $crate::page!(@c [0usize] $($if_t)*);
         const {
             assert!($crate::page!(@c [0usize] $($if_f)*) == $name, "if/else branches must have equal length");
-            assert!(($pe) + $name <= 80usize, "if/else block exceeds end of page");
+            assert!(($pe) + $name <= $crate::display::PAGE_SIZE, "if/else block exceeds end of page");
         };
         if $($cond)* {
             $crate::page!(@s $d [$pe] $($if_t)*);
@@ -418,7 +606,7 @@ macro_rules! page {
         const $name: usize = $crate::page!(@c [0usize] $($first_arm)*);
         const {
             $( assert!($crate::page!(@c [0usize] $($arm)*) == $name, "match arms must have equal length"); )*
-            assert!(($pe) + $name <= 80usize, "match block exceeds end of page");
+            assert!(($pe) + $name <= $crate::display::PAGE_SIZE, "match block exceeds end of page");
         };
         match $e {
             $first_p => { $crate::page!(@s $d [$pe] $($first_arm)*); },
@@ -471,15 +659,39 @@ macro_rules! page {
     (@c [$pe:expr] write $n:literal $_b:expr; $($r:tt)*) => {
         $crate::page!(@c [$pe + $n] $($r)*)
     };
+    (@c [$pe:expr] decimal $_v:expr, $_q:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 7] $($r)*)
+    };
     (@c [$pe:expr] decimal $_v:expr; $($r:tt)*) => {
         $crate::page!(@c [$pe + 7] $($r)*)
     };
+    (@c [$pe:expr] uint left $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 5] $($r)*)
+    };
+    (@c [$pe:expr] sint left $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 6] $($r)*)
+    };
+    (@c [$pe:expr] ulong left $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 10] $($r)*)
+    };
+    (@c [$pe:expr] slong left $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 11] $($r)*)
+    };
     (@c [$pe:expr] uint $_v:expr; $($r:tt)*) => {
         $crate::page!(@c [$pe + 5] $($r)*)
     };
     (@c [$pe:expr] sint $_v:expr; $($r:tt)*) => {
         $crate::page!(@c [$pe + 6] $($r)*)
     };
+    (@c [$pe:expr] ulong $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 10] $($r)*)
+    };
+    (@c [$pe:expr] slong $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 11] $($r)*)
+    };
+    (@c [$pe:expr] duration $_v:expr; $($r:tt)*) => {
+        $crate::page!(@c [$pe + 10] $($r)*)
+    };
     (@c [$pe:expr] byte $_b:literal if $_cond:expr; $($r:tt)*) => {
         $crate::page!(@c [$pe + 1] $($r)*)
     };
@@ -502,7 +714,7 @@ macro_rules! page {
         $crate::page!(@c [$crate::display::PageData::next_line_pos($pe)] $($r)*)
     };
     (@c [$pe:expr] end_page; $($r:tt)*) => {
-        $crate::page!(@c [80] $($r)*)
+        $crate::page!(@c [$crate::display::PAGE_SIZE] $($r)*)
     };
     (@c [$pe:expr] if $_name:ident ($($cond:tt)*) { $($if_t:tt)* } else { $($if_f:tt)* } $($r:tt)*) => {
         $crate::page!(@c [$pe + $crate::page!(@c [0usize] $($if_t)*)] $($r)*)
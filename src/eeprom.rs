@@ -0,0 +1,90 @@
+//! Configuration persistence in the microcontroller's internal EEPROM
+
+use arduino_hal::{pac::EEPROM, Eeprom};
+
+use crate::reset::ResetCause;
+
+/// Offset of the configuration block within the EEPROM
+const CONFIG_OFFSET: u16 = 0;
+
+/// Offset of the little-endian boot counter, just past the configuration block
+const BOOT_COUNT_OFFSET: u16 = CONFIG_OFFSET + 56;
+
+/// Offset of the cause of the last reset, just past the boot counter
+const RESET_CAUSE_OFFSET: u16 = BOOT_COUNT_OFFSET + 2;
+
+/// Internal EEPROM used as a fallback configuration store for boards without a [`DS1307`]
+///
+/// The configuration block uses the exact same 56-byte layout as the [`DS1307`] RAM, so the same
+/// serialized configuration can be stored in either place
+///
+/// [`DS1307`]: crate::rtc::DS1307
+#[must_use]
+pub struct ConfigEeprom {
+    eeprom: Eeprom,
+}
+
+impl ConfigEeprom {
+    /// Take ownership of the EEPROM
+    pub fn new(eeprom: EEPROM) -> Self {
+        Self {
+            eeprom: Eeprom::new(eeprom),
+        }
+    }
+
+    /// Read the stored configuration block
+    ///
+    /// Note: an erased EEPROM reads as all `0xff`, which will fail the configuration's signature
+    /// check
+    #[must_use]
+    pub fn load_config(&self) -> [u8; 56] {
+        let mut data = [0xff; 56];
+        let _ = self.eeprom.read(CONFIG_OFFSET, &mut data);
+        data
+    }
+
+    /// Write the configuration block, skipping every byte that is already up to date
+    ///
+    /// EEPROM cells are only rated for ~100,000 erase/write cycles, so unchanged bytes are never
+    /// rewritten
+    pub fn store_config(&mut self, data: [u8; 56]) {
+        for (i, &byte) in data.iter().enumerate() {
+            let offset = CONFIG_OFFSET + i as u16;
+            if self.eeprom.read_byte(offset) != byte {
+                self.eeprom.write_byte(offset, byte);
+            }
+        }
+    }
+
+    /// Count another boot, returning the new total
+    ///
+    /// An erased counter reads as `0xffff` and is taken as 0. The count stops at 65534 so it can
+    /// never be mistaken for an erased one
+    pub fn increment_boot_count(&mut self) -> u16 {
+        let count = u16::from_le_bytes([
+            self.eeprom.read_byte(BOOT_COUNT_OFFSET),
+            self.eeprom.read_byte(BOOT_COUNT_OFFSET + 1),
+        ]);
+        let count = match count {
+            u16::MAX => 1,
+            count => (count + 1).min(u16::MAX - 1),
+        };
+
+        for (i, byte) in count.to_le_bytes().into_iter().enumerate() {
+            self.eeprom.write_byte(BOOT_COUNT_OFFSET + i as u16, byte);
+        }
+        count
+    }
+
+    /// Record the cause of this reset, returning the one recorded on the previous boot
+    ///
+    /// The byte is only rewritten when the cause changes. An erased record reads as
+    /// [`ResetCause::Unknown`]
+    pub fn swap_reset_cause(&mut self, cause: ResetCause) -> ResetCause {
+        let previous = self.eeprom.read_byte(RESET_CAUSE_OFFSET);
+        if previous != cause as u8 {
+            self.eeprom.write_byte(RESET_CAUSE_OFFSET, cause as u8);
+        }
+        ResetCause::from_byte(previous)
+    }
+}
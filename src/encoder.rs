@@ -7,13 +7,19 @@ use arduino_hal::{
     pac::{EXINT, TC4},
     port::{
         mode::{Floating, Input, PullUp},
-        Pin,
+        Pin, PinOps,
     },
 };
 use avr_device::interrupt::{CriticalSection, Mutex};
 
 const COOLDOWN: u8 = 5;
 
+/// Quarter steps moved for each transition, indexed by `previous_state << 2 | current_state`
+///
+/// Transitions that skip a state can only be caused by contact bounce or missed samples, so they
+/// are ignored
+const QUARTER_STEPS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
 static QUEUED_CLICKS: Mutex<Cell<i8>> = Mutex::new(Cell::new(0));
 static QUEUED_PRESSES: Mutex<Cell<u8>> = Mutex::new(Cell::new(0));
 
@@ -157,6 +163,16 @@ impl Encoder {
         })
     }
 
+    /// Checks if the button is currently held down
+    #[must_use]
+    pub fn is_held(&self) -> bool {
+        avr_device::interrupt::free(|cs| {
+            BTN.borrow(cs)
+                .get()
+                .is_some_and(Pin::<Input<PullUp>, PB4>::is_low)
+        })
+    }
+
     /// Returns which direction the encoder was last clicked, if any
     #[must_use]
     pub fn next_click(&self) -> Option<Click> {
@@ -176,3 +192,53 @@ impl Encoder {
         })
     }
 }
+
+/// Polled quadrature decoder for a rotary encoder on any two spare pins
+///
+/// Unlike [`Encoder`], this requires no interrupts or timers, but [`RotaryEncoder::poll`] must be
+/// called often enough to observe every quarter step of the rotation
+#[must_use]
+pub struct RotaryEncoder<A, B> {
+    pin_a: Pin<Input<PullUp>, A>,
+    pin_b: Pin<Input<PullUp>, B>,
+    state: u8,
+    quarter_steps: i8,
+}
+
+impl<A, B> RotaryEncoder<A, B>
+where
+    A: PinOps,
+    B: PinOps,
+{
+    /// Bind encoder to pins and initialize at rest
+    pub fn new(pin_a: Pin<Input<Floating>, A>, pin_b: Pin<Input<Floating>, B>) -> Self {
+        let mut encoder = Self {
+            pin_a: pin_a.into_pull_up_input(),
+            pin_b: pin_b.into_pull_up_input(),
+            state: 0,
+            quarter_steps: 0,
+        };
+        encoder.state = encoder.read_state();
+        encoder
+    }
+
+    fn read_state(&self) -> u8 {
+        (self.pin_a.is_high() as u8) << 1 | self.pin_b.is_high() as u8
+    }
+
+    /// Sample the pins and return the net number of detents turned since the last call
+    ///
+    /// The sign depends on which way round the encoder's A and B contacts are wired
+    ///
+    /// Note: partial detents are carried over to the next call, so a bouncing contact can never
+    /// register a detent on its own
+    pub fn poll(&mut self) -> i8 {
+        let state = self.read_state();
+        self.quarter_steps += QUARTER_STEPS[(self.state << 2 | state) as usize];
+        self.state = state;
+
+        let detents = self.quarter_steps / 4;
+        self.quarter_steps %= 4;
+        detents
+    }
+}
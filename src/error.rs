@@ -0,0 +1,33 @@
+//! Unified error type for the climate controller
+
+use arduino_hal::i2c;
+
+/// Any failure or fault condition the [`ClimateController`] can encounter
+///
+/// [`ClimateController`]: crate::ClimateController
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerError {
+    /// Communication with a device on the I2C bus failed
+    I2c(i2c::Error),
+
+    /// A sensor required for control is reporting an invalid reading
+    SensorFault,
+
+    /// The habitat is dangerously far above its target temperature
+    OverTemp,
+
+    /// The compressor is running but the coolant has not responded
+    CompressorFault,
+
+    /// Stored configuration failed its signature check
+    InvalidConfig,
+
+    /// Temperature readings disagree in a way that suggests sensors were swapped during assembly
+    SensorPlacement,
+}
+
+impl From<i2c::Error> for ControllerError {
+    fn from(value: i2c::Error) -> Self {
+        Self::I2c(value)
+    }
+}
@@ -0,0 +1,105 @@
+//! PCF8574 I2C GPIO expander driving an additional bank of relays
+
+use arduino_hal::I2c;
+use embedded_hal::i2c::I2c as I2cTrait;
+
+use crate::{control::Switchable, rtc::I2cResult};
+
+/// Address of a PCF8574 with A0-A2 tied low; a PCF8574A starts at 0x38 instead
+pub const PCF8574_ADDR: u8 = 0x20;
+
+/// Bank of up to 8 relays on a PCF8574 expander
+///
+/// Channels don't touch the bus: switching one only updates the bank's staged outputs, which
+/// [`RelayBank::flush`] then writes out in a single transaction. The bank therefore needs no bus of
+/// its own and can share it with the [`DS1307`](crate::rtc::DS1307), much like the
+/// [`ClimatePwm`](crate::control::ClimatePwm) is lent to a
+/// [`CompressorDrive`](crate::control::CompressorDrive)
+///
+/// The PCF8574 powers up with every pin high, so with active-low relay boards (the common kind)
+/// all channels start off
+#[must_use]
+pub struct RelayBank {
+    addr: u8,
+    active_low: bool,
+    outputs: u8,
+    written: Option<u8>,
+}
+
+impl RelayBank {
+    /// Drive the expander at the given address with all channels off
+    pub const fn new(addr: u8, active_low: bool) -> Self {
+        Self {
+            addr,
+            active_low,
+            outputs: 0,
+            written: None,
+        }
+    }
+
+    /// Access channel `n`, wrapping to the range `0..8`
+    pub const fn channel(&mut self, n: u8) -> BankRelay<'_> {
+        BankRelay {
+            outputs: &mut self.outputs,
+            mask: 1 << (n & 7),
+        }
+    }
+
+    /// Staged channel states, one bit per channel
+    #[must_use]
+    pub const fn outputs(&self) -> u8 {
+        self.outputs
+    }
+
+    /// Write the staged channel states to the expander, if they differ from what was last written
+    ///
+    /// # Errors
+    /// Returns an error if something goes wrong on the I2C bus, in which case the write is
+    /// retried on the next flush
+    pub fn flush(&mut self, i2c: &mut I2c) -> I2cResult {
+        if self.written == Some(self.outputs) {
+            return Ok(());
+        }
+
+        let byte = if self.active_low {
+            !self.outputs
+        } else {
+            self.outputs
+        };
+        self.written = None;
+        i2c.write(self.addr, &[byte])?;
+        self.written = Some(self.outputs);
+
+        Ok(())
+    }
+}
+
+/// Single channel of a [`RelayBank`]
+///
+/// Changes take effect on the bank's next [`RelayBank::flush`]
+#[must_use]
+pub struct BankRelay<'a> {
+    outputs: &'a mut u8,
+    mask: u8,
+}
+
+impl Switchable for BankRelay<'_> {
+    fn activate(&mut self, _now: u32) -> bool {
+        let was_off = *self.outputs & self.mask == 0;
+        *self.outputs |= self.mask;
+        was_off
+    }
+
+    fn deactivate(&mut self, _now: u32) -> bool {
+        let was_on = *self.outputs & self.mask != 0;
+        *self.outputs &= !self.mask;
+        was_on
+    }
+
+    fn is_active(&self) -> bool {
+        *self.outputs & self.mask != 0
+    }
+
+    /// Expander outputs aren't verified, so there is nothing to do over time
+    fn tick(&mut self, _now: u32) {}
+}
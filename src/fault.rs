@@ -0,0 +1,120 @@
+//! Debounced fault detection
+
+/// Fault detector with separate entry and clearing thresholds
+///
+/// The fault is raised once the monitored value has stayed above the entry threshold for the
+/// debounce count of consecutive updates, and cleared once it has stayed below the (lower)
+/// clearing threshold for as long. A value hovering at either threshold therefore can't make the
+/// fault flap. NaN values neither raise nor clear the fault
+///
+/// Faults triggered by a value falling below a threshold can be monitored by negating the value
+/// and thresholds
+#[must_use]
+pub struct FaultLatch {
+    enter: f32,
+    clear: f32,
+    debounce: u8,
+    count: u8,
+    active: bool,
+}
+
+impl FaultLatch {
+    /// Create an inactive latch
+    ///
+    /// `clear` should be no greater than `enter`; a `debounce` of 0 or 1 latches on the first
+    /// update past a threshold
+    pub const fn new(enter: f32, clear: f32, debounce: u8) -> Self {
+        Self {
+            enter,
+            clear,
+            debounce,
+            count: 0,
+            active: false,
+        }
+    }
+
+    /// Feed in the latest value, returning whether the fault is active
+    pub fn update(&mut self, value: f32) -> bool {
+        let crossing = if self.active {
+            value < self.clear
+        } else {
+            value > self.enter
+        };
+
+        if crossing {
+            self.count = self.count.saturating_add(1);
+            if self.count >= self.debounce {
+                self.active = !self.active;
+                self.count = 0;
+            }
+        } else {
+            self.count = 0;
+        }
+
+        self.active
+    }
+
+    /// Checks if the fault is active
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Clear the fault and any partial debounce count
+    pub const fn reset(&mut self) {
+        self.count = 0;
+        self.active = false;
+    }
+}
+
+/// Tolerance for sporadic failures of a polled peripheral, e.g. a device on a marginal I2C bus
+///
+/// A failed read is replaced with the last successful one until `limit` consecutive reads have
+/// failed, so a single glitch doesn't flash an error onto the display or drop a feature that
+/// depends on the reading. Only once the failures persist is the error passed on
+#[must_use]
+pub struct LastGood<T> {
+    last: Option<T>,
+    failures: u8,
+    limit: u8,
+}
+
+impl<T> LastGood<T>
+where
+    T: Copy,
+{
+    /// Start without a good value, passing errors on after `limit` consecutive failures
+    ///
+    /// Until the first successful read every error is passed on; a `limit` of 0 or 1 passes on
+    /// every error
+    pub const fn new(limit: u8) -> Self {
+        Self {
+            last: None,
+            failures: 0,
+            limit,
+        }
+    }
+
+    /// Feed in the latest read, returning it if it succeeded, or otherwise the last good value
+    /// unless the failures have persisted for too long
+    ///
+    /// # Errors
+    /// Returns the error of the latest read once `limit` consecutive reads have failed, or if no
+    /// read has succeeded yet
+    pub fn update<E>(&mut self, result: Result<T, E>) -> Result<T, E> {
+        match result {
+            Ok(value) => {
+                self.last = Some(value);
+                self.failures = 0;
+                Ok(value)
+            }
+            Err(err) => {
+                self.failures = self.failures.saturating_add(1);
+                match self.last {
+                    Some(last) if self.failures < self.limit => Ok(last),
+                    _ => Err(err),
+                }
+            }
+        }
+    }
+}
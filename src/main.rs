@@ -8,8 +8,8 @@
 
 use arduino_hal::{
     entry,
-    hal::port::{PC6, PC7, PD4, PD5, PD6, PD7, PF6, PF7},
-    pac::TC0,
+    hal::port::{PC7, PD4, PD5, PD6, PD7},
+    pac::{CPU, TC0},
     port::{
         mode::{Floating, Input},
         Pin,
@@ -18,33 +18,296 @@ use arduino_hal::{
 };
 use panic_halt as _;
 
+pub mod button;
+pub mod buzzer;
 mod codegen;
 pub mod control;
 pub mod display;
+pub mod eeprom;
 pub mod encoder;
+pub mod error;
+pub mod expander;
+pub mod fault;
 pub mod millis;
+pub mod reset;
 pub mod rtc;
+pub mod scheduler;
 pub mod sens;
 pub mod utils;
 
 use crate::{
-    control::{PWMController, Relay},
-    display::{Display, PageData},
+    buzzer::Buzzer,
+    control::{
+        ClimatePwm, CompressorDrive, DutyWindow, EnergyMeter, PWMController, PwmChannel, Relay,
+        Switchable,
+    },
+    display::{Display, PageData, COLS, GLYPH_DOWN, GLYPH_UP, PAGE_SIZE},
+    eeprom::ConfigEeprom,
     encoder::{Click, Encoder},
-    millis::{init_millis, millis},
-    rtc::{Date, Month, RTCTime, DS1307},
-    sens::Sensorium,
-    utils::{i16_to_f32, is_finite, recip, u16_to_f32},
+    error::ControllerError,
+    fault::{FaultLatch, LastGood},
+    millis::{init_millis, micros, millis},
+    reset::ResetCause,
+    rtc::{Date, Hours, I2cResult, Minutes, Month, RTCTime, DS1307},
+    scheduler::{Scheduler, WeeklySchedule},
+    sens::{AuxInput, Potentiometer, Readings, Sensorium, Temperature},
+    utils::{dew_point, i16_to_f32, is_finite, recip, remap, u16_to_f32},
 };
 
-const PWM_HZ: u16 = 31_250;
+/// Timing and tuning parameters of the [`ClimateController`]
+///
+/// Unlike the [`ControllerConfig`], these are fixed at construction and not user-editable.
+/// Temperatures used by the control logic are in degrees celsius, the unit it works in; only those
+/// concerning the display are in degrees fahrenheit, the unit it shows
+#[derive(Clone, Copy)]
+#[must_use]
+pub struct ControllerParams {
+    /// Frequency of the fan and pump PWM outputs in hertz
+    pub pwm_hz: u16,
+
+    /// Milliseconds between sensor samples
+    pub sample_interval: u32,
+
+    /// Sensor samples per second taken by a timer interrupt instead of the main loop, for a steady
+    /// cadence however long each pass of the loop takes; 0 samples from the main loop every
+    /// `sample_interval`. Clamped to 4 to 1000, see [`sens::BACKGROUND_HZ`]
+    pub background_sample_hz: u16,
+
+    /// Time constant in milliseconds of the sensor filters once settled, which keeps their
+    /// smoothing the same however the sample interval varies; 0 weights every sample equally
+    /// instead, smoothing over about 1023 samples
+    pub filter_time_constant: u32,
+
+    /// Milliseconds between control updates
+    pub update_interval: u32,
+
+    /// Longest interval in milliseconds between control updates; while the system is quiet, the
+    /// interval doubles after each update up to this, and drops back to `update_interval` as soon
+    /// as anything approaches a threshold. Equal to `update_interval` to disable
+    pub max_update_interval: u32,
+
+    /// Whether control updates act on the mean of the readings sampled since the previous update,
+    /// rather than on the latest filtered readings alone
+    pub average_samples: bool,
+
+    /// Degrees celsius within which the habitat must be of its target, and outside of which the
+    /// coolant must be from the compressor's thresholds, for the system to be considered quiet
+    pub quiet_band: f32,
+
+    /// Whether to idle the CPU between interrupts; the timers keep running, so the millisecond tick
+    /// still wakes it for every sample
+    pub idle_sleep: bool,
+
+    /// Milliseconds between display refreshes
+    pub display_interval: u32,
+
+    /// Milliseconds between target recalculations and configuration saves
+    pub config_interval: u32,
+
+    /// Sensitivity (0 to 1) of the filter on the setpoint potentiometer, which is sampled with each
+    /// target recalculation
+    pub setpoint_pot_sens: f32,
+
+    /// ADC counts the filtered setpoint potentiometer must drift before the target follows it
+    pub setpoint_pot_deadband: f32,
+
+    /// Milliseconds after startup before regulation begins, giving the sensors time to settle
+    pub calibration_period: u32,
+
+    /// Lowest nonzero coolant pump duty that reliably starts the pump
+    pub pump_min_duty: u16,
+
+    /// Largest change in coolant pump duty per update, so it soft-starts rather than jumping
+    /// straight to full speed and drawing a large inrush; 0 disables ramping
+    pub pump_slew: u16,
+
+    /// Lowest nonzero habitat fan duty at which the fan doesn't stall
+    pub habitat_fan_min_duty: u16,
+
+    /// Duties above the first of which the habitat fan switches fully on, and below the second of
+    /// which fully off, for fans that can't run proportionally at all; `None` drives it
+    /// proportionally above `habitat_fan_min_duty`
+    pub habitat_fan_switching: Option<(u16, u16)>,
+
+    /// Largest change in condenser fan duty per update, so it ramps smoothly instead of hunting
+    /// when the condenser sits at a threshold; 0 disables smoothing
+    pub condenser_fan_slew: u16,
+
+    /// Milliseconds the condenser fan keeps running after the compressor stops, since the sensed
+    /// condenser temperature lags behind the head pressure; 0 disables
+    pub condenser_post_run: u32,
+
+    /// Lowest condenser fan duty held during the post-run period
+    pub condenser_post_run_duty: u16,
 
-const SAMPLE_INTERVAL: u32 = 1;
-const UPDATE_INTERVAL: u32 = 10;
-const DISPLAY_INTERVAL: u32 = 100;
-const CONFIG_INTERVAL: u32 = 1000;
+    /// Coolant pump duty run while the sensors settle before regulation begins, remapped above
+    /// `pump_min_duty` like every other pump duty, so 1 runs it at the minimum; 0 disables
+    pub warmup_pump_duty: u16,
 
-const CALIBRATION_PERIOD: u32 = 2000;
+    /// Habitat fan duty run while the sensors settle before regulation begins; 0 disables
+    pub warmup_fan_duty: u16,
+
+    /// Degrees celsius either side of target within which the habitat fan holds its state; it
+    /// switches on above the band and off below it
+    pub habitat_fan_deadband: f32,
+
+    /// Whether the LCD is fitted; without it the controller runs headless, sending nothing to the
+    /// display, with faults still announced on the buzzer
+    pub display_present: bool,
+
+    /// Milliseconds after the last user input before the backlight may switch off at night
+    pub backlight_timeout: u32,
+
+    /// Hundredths of a degree that displayed temperature readings are rounded to a multiple of, so
+    /// residual filter noise doesn't flicker in the last digit; 1 shows full precision
+    pub display_quantum: u8,
+
+    /// Rate of change in degrees fahrenheit per second below which a temperature is shown as
+    /// steady
+    pub trend_threshold: f32,
+
+    /// Degrees celsius above target at which the habitat is considered dangerously overheated
+    pub over_temp_margin: f32,
+
+    /// Degrees celsius below `over_temp_margin` the habitat must fall to clear an
+    /// over-temperature fault
+    pub over_temp_band: f32,
+
+    /// Consecutive updates the habitat must stay past either over-temperature threshold before the
+    /// fault is raised or cleared
+    pub over_temp_debounce: u8,
+
+    /// Consecutive updates a required sensor must stay failed, or recovered, before the sensor
+    /// fault is raised or cleared
+    pub sensor_fault_debounce: u8,
+
+    /// Degrees celsius the temperature readings may differ by while everything is expected to be
+    /// near ambient, before the sensors are suspected of having been swapped; 0 disables the check
+    pub placement_band: f32,
+
+    /// Milliseconds the compressor must have been off, or the controller running without it,
+    /// before the readings are expected to have settled to ambient
+    pub placement_settle: u32,
+
+    /// Consecutive updates the coolant must read warmer than the condenser while the compressor is
+    /// cooling, or recover, before a sensor placement fault is raised or cleared
+    pub placement_debounce: u8,
+
+    /// Milliseconds after cut-in within which the coolant must have dropped by
+    /// `compressor_min_drop`
+    pub compressor_response_timeout: u32,
+
+    /// Degrees celsius the coolant must drop after cut-in for the compressor to be considered
+    /// working
+    pub compressor_min_drop: f32,
+
+    /// Milliseconds the condenser fan and coolant pump keep running after the compressor is
+    /// stopped during a shutdown, to dissipate head pressure before the master relay is dropped
+    pub shutdown_cooldown: u32,
+
+    /// Milliseconds after entering or leaving service mode during which further presses on the
+    /// Service page are ignored, so a double press can't bounce straight back out
+    pub service_guard: u32,
+
+    /// Degrees celsius above the habitat's dew point below which the coolant is never chilled,
+    /// when the habitat humidity is known
+    pub dew_point_margin: f32,
+
+    /// Degrees celsius below which the coolant is considered at risk of freezing; the compressor is
+    /// stopped and kept off whenever the coolant is colder, regardless of any other logic
+    pub freeze_threshold: f32,
+
+    /// Gain applied to the coolant's rate of change when anticipating where it is headed, so the
+    /// compressor cuts out early while the coolant is falling fast and its thermal mass carries it
+    /// the rest of the way; 0 compares the thresholds against the coolant as measured
+    pub coolant_anticipation: f32,
+
+    /// Seconds ahead the coolant temperature is projected along its rate of change before being
+    /// compared against the compressor thresholds
+    pub coolant_lookahead: f32,
+
+    /// Consecutive failed clock reads tolerated, each replaced by the last good reading, before the
+    /// clock is reported as not responding
+    pub rtc_retries: u8,
+
+    /// Seconds the compressor must stay off before it may restart, to protect it from starting
+    /// against high head pressure; also applies from startup
+    pub compressor_lockout: u16,
+}
+
+impl ControllerParams {
+    /// Parameters tuned for the original build
+    pub const DEFAULT: Self = Self {
+        pwm_hz: 31_250,
+
+        sample_interval: 1,
+        background_sample_hz: 0,
+        filter_time_constant: 0,
+        update_interval: 10,
+        max_update_interval: 160,
+        average_samples: false,
+        quiet_band: 0.277_778,
+        idle_sleep: true,
+        display_interval: 100,
+        config_interval: 1000,
+        setpoint_pot_sens: 0.25,
+        setpoint_pot_deadband: 4.0,
+
+        calibration_period: 2000,
+
+        pump_min_duty: 80,
+        pump_slew: 8,
+        habitat_fan_min_duty: 180,
+        habitat_fan_switching: None,
+        condenser_fan_slew: 2,
+        condenser_post_run: 60_000,
+        condenser_post_run_duty: 128,
+        warmup_pump_duty: 1,
+        warmup_fan_duty: 0,
+        habitat_fan_deadband: 0.027_778,
+
+        display_present: true,
+        backlight_timeout: 30_000,
+        display_quantum: 10,
+        trend_threshold: 0.005,
+
+        over_temp_margin: 2.777_778,
+        over_temp_band: 0.555_556,
+        over_temp_debounce: 100,
+        sensor_fault_debounce: 50,
+        placement_band: 8.333_333,
+        placement_settle: 1_800_000,
+        placement_debounce: 100,
+        compressor_response_timeout: 600_000,
+        compressor_min_drop: 0.555_556,
+        shutdown_cooldown: 120_000,
+        service_guard: 2000,
+
+        dew_point_margin: 1.111_111,
+        freeze_threshold: 2.222_222,
+        coolant_anticipation: 0.0,
+        coolant_lookahead: 0.0,
+
+        rtc_retries: 5,
+        compressor_lockout: 180,
+    };
+}
+
+impl Default for ControllerParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Degrees celsius below the cut-in point at which the compressor cuts out (10F)
+const CUT_OUT_BAND: f32 = 5.555_556;
+
+/// Condenser temperature in degrees celsius above which the condenser fan ramps up over the next
+/// 5.56C (10F), and which a running compressor must bring the condenser to (80F)
+const CONDENSER_HOT: f32 = 26.666_667;
+
+/// Condenser temperature in degrees celsius at which the condenser fan reaches full speed (90F)
+const CONDENSER_FULL: f32 = CONDENSER_HOT + 5.555_556;
 
 crate::codegen::portable!(
     /// Portable configuration for the [`ClimateController`]
@@ -52,6 +315,23 @@ crate::codegen::portable!(
     /// The diapause season runs from `diapause_start_month`/`diapause_start_day` to
     /// `diapause_end_month`/`diapause_end_day`, with `diapause_ramp_days` days of linear
     /// interpolation at each end
+    ///
+    /// A `coolant_target` above 0 has the compressor hold the coolant loop at that temperature
+    /// independently of the habitat target; at 0 the compressor instead cuts in once the coolant is
+    /// less than `min_effective_subcooling` (tuned automatically) below the habitat target
+    ///
+    /// `clock_12h` shows the time as e.g. `01:45 PM` instead of `13:45:00`; the seconds are dropped
+    /// to leave room for the meridiem
+    ///
+    /// The `*_trim` fields are added to the readings of each sensor, in degrees fahrenheit like
+    /// the other temperatures here, to correct small systematic errors
+    ///
+    /// The `*_watts` fields give the nominal power of each load at full duty, from which the energy
+    /// used is estimated; loads left at 0 aren't counted
+    ///
+    /// A `setpoint_pot` of 1 or 5 reads a potentiometer on aux input A1 or A5 as the target, from
+    /// `setpoint_pot_min` at one end of its travel to `setpoint_pot_max` at the other, in place of
+    /// the day/night cycle and weekly program; any other value leaves the knob unused
     #[derive(Clone)]
     pub struct ControllerConfig {
         day_temp as DayTemp: f32 = 75.0,
@@ -65,6 +345,23 @@ crate::codegen::portable!(
         diapause_ramp_days as DiapauseRampDays: u8 = 14,
 
         min_effective_subcooling as MinSubcooling: f32 = 8.0,
+        coolant_target as CoolantTarget: f32 = 0.0,
+
+        clock_12h as Clock12Hour: bool = false,
+
+        coolant_trim as CoolantTrim: f32 = 0.0,
+        habitat_trim as HabitatTrim: f32 = 0.0,
+        condenser_trim as CondenserTrim: f32 = 0.0,
+
+        compressor_watts as CompressorWatts: u16 = 0,
+        heater_watts as HeaterWatts: u16 = 0,
+        condenser_fan_watts as CondenserFanWatts: u16 = 0,
+        habitat_fan_watts as HabitatFanWatts: u16 = 0,
+        coolant_pump_watts as CoolantPumpWatts: u16 = 0,
+
+        setpoint_pot as SetpointPot: u8 = 0,
+        setpoint_pot_min as SetpointPotMin: u8 = 50,
+        setpoint_pot_max as SetpointPotMax: u8 = 65,
     }
     exit = b"[Exit Config]";
     info = b"  Press To Confirm  ";
@@ -101,6 +398,15 @@ impl ControllerConfig {
     /// Calculate the target temperature for the given time based on the current configuration
     #[must_use]
     pub const fn calculate_target(&self, time: RTCTime) -> f32 {
+        self.calculate_target_with_program(time, None)
+    }
+
+    /// Calculate the target temperature for the given time, with `program` (e.g. from a
+    /// [`WeeklySchedule`]) taking the place of the day/night cycle if given
+    ///
+    /// The diapause season and its ramps still apply on top of the program
+    #[must_use]
+    pub const fn calculate_target_with_program(&self, time: RTCTime, program: Option<f32>) -> f32 {
         const INV_24: f32 = 1.0 / 24.0;
         const INV_21600: f32 = 1.0 / 21_600.0;
         const INV_86400: f32 = 1.0 / 86_400.0;
@@ -115,7 +421,9 @@ impl ControllerConfig {
         let clock_hour = if hour < 12 { hour } else { hour - 12 };
         let secs_of_hour = time.seconds.bin() as u16 + time.minutes.bin() as u16 * 60;
 
-        let diurnal_cycle_temp = if clock_hour >= 6 {
+        let diurnal_cycle_temp = if let Some(temp) = program {
+            temp
+        } else if clock_hour >= 6 {
             let prog =
                 i16_to_f32((secs_of_hour + (clock_hour - 6) as u16 * 3600) as i16) * INV_21600;
             let smooth = (3.0 - prog * 2.0) * prog * prog;
@@ -204,6 +512,11 @@ crate::codegen::revolving_enum!(
     enum PageId {
         TimeAndTarget,
         TempReadings,
+        Extremes,
+        Diagnostics,
+        Resets,
+        Energy,
+        Service,
         Configuration,
         ManualControl,
     }
@@ -299,6 +612,17 @@ impl Duty {
     }
 }
 
+/// Action the [`ClimateController`] must take in response to a button press
+enum PressAction {
+    None,
+    ConfigChanged,
+    EnterManual,
+    ResetExtremes,
+    ResetEnergy,
+    Resettle,
+    ToggleService,
+}
+
 enum UIMode<'a> {
     Normal(&'a mut PageId),
     Select(&'a mut PageId, &'a mut SelectIndex),
@@ -366,12 +690,12 @@ impl UIState {
         &mut self,
         config: &mut ControllerConfig,
         control: &ControlState,
-    ) -> (bool, bool) {
+    ) -> PressAction {
         if self.control_buffer.take().is_some() {
-            (false, false)
+            PressAction::None
         } else if let Some(buffer) = self.edit_buffer.take() {
             config.set_buffer(buffer);
-            (true, false)
+            PressAction::ConfigChanged
         } else if let Some(ref mut select_idx) = self.select_idx {
             if matches!(self.page, PageId::Configuration) {
                 self.edit_buffer = config.get_buffer(select_idx.idx);
@@ -384,15 +708,23 @@ impl UIState {
                     self.select_idx = None;
                 }
             }
-            (false, false)
+            PressAction::None
         } else if matches!(self.page, PageId::Configuration) {
             self.select_idx = Some(SelectIndex::new_config());
-            (false, false)
+            PressAction::None
         } else if matches!(self.page, PageId::ManualControl) {
             self.select_idx = Some(SelectIndex::new_control());
-            (false, true)
+            PressAction::EnterManual
+        } else if matches!(self.page, PageId::Extremes) {
+            PressAction::ResetExtremes
+        } else if matches!(self.page, PageId::Energy) {
+            PressAction::ResetEnergy
+        } else if matches!(self.page, PageId::TempReadings) {
+            PressAction::Resettle
+        } else if matches!(self.page, PageId::Service) {
+            PressAction::ToggleService
         } else {
-            (false, false)
+            PressAction::None
         }
     }
 
@@ -413,15 +745,16 @@ enum HabitatCondition {
 }
 
 impl HabitatCondition {
+    /// Classify the habitat temperature against the target, both in degrees celsius
     const fn test(habitat: f32, target: f32) -> Self {
         let delta = habitat - target;
-        if delta < -0.25 {
+        if delta < -0.138_889 {
             Self::TooCold
-        } else if delta < -0.05 {
+        } else if delta < -0.027_778 {
             Self::Cool
-        } else if delta < 0.05 {
+        } else if delta < 0.027_778 {
             Self::JustRight
-        } else if delta < 0.25 {
+        } else if delta < 0.138_889 {
             Self::Warm
         } else {
             Self::TooHot
@@ -463,6 +796,133 @@ impl HabitatCondition {
     }
 }
 
+/// Character showing whether a temperature with the given slope is rising, falling, or steady,
+/// given the slope below which it is considered steady
+const fn trend_arrow(slope: f32, threshold: f32) -> u8 {
+    if slope > threshold {
+        GLYPH_UP
+    } else if slope < -threshold {
+        GLYPH_DOWN
+    } else {
+        b' '
+    }
+}
+
+/// Watt-hours as kilowatt-hours, for display
+const fn kwh(wh: u32) -> f32 {
+    let wh = if wh > 999_999 { 999_999 } else { wh };
+    u16_to_f32((wh / 1000) as u16) + u16_to_f32((wh % 1000) as u16) * 0.001
+}
+
+/// Progress of confirming that the running compressor is actually cooling the coolant loop
+#[derive(Clone, Copy)]
+enum CompressorCheck {
+    /// Compressor is off
+    Idle,
+    /// Compressor cut in at the given time with the coolant at the given temperature
+    Watching(u32, f32),
+    /// Coolant has responded since cut-in
+    Cooling,
+    /// Coolant failed to respond within the compressor response timeout
+    Stalled,
+}
+
+impl CompressorCheck {
+    /// Advance the check given whether the compressor is running and the current coolant
+    /// temperature
+    fn next(self, params: &ControllerParams, now: u32, running: bool, coolant: f32) -> Self {
+        match self {
+            _ if !running => Self::Idle,
+            Self::Idle if is_finite(coolant) => Self::Watching(now, coolant),
+            Self::Watching(_, start) if coolant <= start - params.compressor_min_drop => {
+                Self::Cooling
+            }
+            Self::Watching(since, _)
+                if now.wrapping_sub(since) >= params.compressor_response_timeout =>
+            {
+                Self::Stalled
+            }
+            check => check,
+        }
+    }
+}
+
+/// Operating state of the [`ClimateController`], deciding what each control step does
+#[derive(Clone, Copy)]
+enum ControllerState {
+    /// Constructed but not yet started; nothing is switched
+    Init,
+    /// Sensors settling and no target set yet; coolant and air circulate at the warm-up duties
+    Grace,
+    /// Regulating normally
+    Running,
+    /// Compressor stopped at the given time, fans and pump still running
+    ShuttingDown(u32),
+    /// Master relay dropped; nothing more will be switched until reset
+    Shutdown,
+    /// Every output parked for servicing; sampling and the display carry on
+    Service,
+}
+
+impl ControllerState {
+    const fn is_shut_down(self) -> bool {
+        matches!(self, Self::ShuttingDown(_) | Self::Shutdown)
+    }
+}
+
+/// Outputs pinned by hand, each taking precedence over the automatic control logic while `Some`
+#[derive(Clone, Copy)]
+struct Overrides {
+    compressor: Option<bool>,
+    heater: Option<bool>,
+    duty: [Option<u16>; 3],
+}
+
+impl Overrides {
+    const NONE: Self = Self {
+        compressor: None,
+        heater: None,
+        duty: [None; 3],
+    };
+
+    const fn is_any(&self) -> bool {
+        self.compressor.is_some()
+            || self.heater.is_some()
+            || self.duty[0].is_some()
+            || self.duty[1].is_some()
+            || self.duty[2].is_some()
+    }
+
+    /// The duty to run `channel` at, given the duty demanded by the control logic
+    const fn duty(&self, channel: PwmChannel, auto: u16) -> u16 {
+        match self.duty[channel as usize] {
+            Some(duty) => duty,
+            None => auto,
+        }
+    }
+}
+
+/// Snapshot of the low-level state shown on the diagnostics page
+struct Diagnostics {
+    /// Filtered ADC samples of the coolant, habitat and condenser sensors
+    adc: [u16; 3],
+    /// Duty of PWM channels A, B and C in percent
+    duty_pct: [u16; 3],
+    /// Compressor, heater, relay 2 and master relay states
+    relays: [bool; 4],
+    /// Worst-case main loop iteration time in microseconds, saturated to fit
+    max_loop_us: u16,
+    /// Percentage of the last 10 minutes the compressor has been on
+    compressor_pct: u8,
+}
+
+/// Time-of-day automation run by the [`ClimateController`]'s [`Scheduler`]
+#[derive(Clone, Copy)]
+enum ScheduledAction {
+    /// Start each day with fresh temperature extremes
+    ResetExtremes,
+}
+
 /// Formicarium climate control system state machine
 ///
 /// # Pin Configuration
@@ -478,7 +938,7 @@ impl HabitatCondition {
 /// - `PB7`: PWM channel C (circulation pump)
 ///
 /// `PORTC`:
-/// - `PC6`: unused
+/// - `PC6`: active buzzer
 /// - `PC7`: RTC square wave input
 ///
 /// `PORTD`:
@@ -496,45 +956,56 @@ impl HabitatCondition {
 /// - `PE6`: rotary encoder B
 ///
 /// `PORTF`:
-/// - `PF0`: thermistor (unused)
+/// - `PF0`: thermistor (unused); auxiliary analog input
 /// - `PF1`: thermistor (condenser)
 /// - `PF4`: thermistor (formicarium)
 /// - `PF5`: thermistor (coolant loop)
-/// - `PF6`: unused
-/// - `PF7`: unused
+/// - `PF6`: auxiliary analog input
+/// - `PF7`: LCD backlight
 ///
 /// [^1]: board modified to break `PD5` out to the factory NC pin that would be A7\
 /// [^2]: board modified to break `PE2` out to the factory NC pin that would be A6
 #[must_use]
 pub struct ClimateController {
+    params: ControllerParams,
+
     sensorium: Sensorium,
 
-    compressor: Relay<PD4>,
+    compressor: CompressorDrive<PD4>,
+    compressor_duty: DutyWindow<10>,
+    energy: EnergyMeter<5>,
     heater: Relay<PD5>,
-    _relay2: Relay<PD6>,
+    relay2: Relay<PD6>,
     master_120vac: Relay<PD7>,
 
-    pwm: PWMController,
+    pwm: ClimatePwm,
 
     rtc: DS1307,
+    rtc_time: LastGood<Option<RTCTime>>,
     _sqw: Pin<Input<Floating>, PC7>,
 
+    eeprom: ConfigEeprom,
+
+    scheduler: Scheduler<ScheduledAction, 4>,
+    weekly: WeeklySchedule<4>,
+
     encoder: Encoder,
 
-    _pc6: Pin<Input<Floating>, PC6>,
-    _pf6: Pin<Input<Floating>, PF6>,
-    _pf7: Pin<Input<Floating>, PF7>,
+    buzzer: Buzzer,
 
     display: Display,
 
     tc0: TC0,
+    cpu: CPU,
 
     next_sample: u32,
     next_update: u32,
+    update_interval: u32,
     next_display: u32,
     next_config: u32,
 
     target_temp: Target,
+    setpoint_pot: Potentiometer,
 
     config: ControllerConfig,
     config_changed: bool,
@@ -544,11 +1015,32 @@ pub struct ClimateController {
     ui_state: UIState,
 
     last_condition: HabitatCondition,
+    last_fault: Option<ControllerError>,
+    compressor_check: CompressorCheck,
+    over_temp: FaultLatch,
+    sensor_fault: FaultLatch,
+    placement_fault: FaultLatch,
+    spread_fault: FaultLatch,
+    state: ControllerState,
+    overrides: Overrides,
+    service_toggled: Option<u32>,
+
+    last_input: u32,
+    humidity: Option<f32>,
+    habitat_fan_on: bool,
+    night: bool,
+
+    max_loop_us: u32,
+
+    reset_cause: ResetCause,
+    previous_reset_cause: ResetCause,
+    boot_count: u16,
 }
 
 impl ClimateController {
-    /// Construct and initialize climate controller and interface with hardware
-    pub fn new(periphs: Peripherals) -> Self {
+    /// Construct and initialize climate controller with the given parameters and interface with
+    /// hardware
+    pub fn new(periphs: Peripherals, params: ControllerParams) -> Self {
         let pins = arduino_hal::hal::Pins::new(
             periphs.PORTB,
             periphs.PORTC,
@@ -560,15 +1052,43 @@ impl ClimateController {
         // Disable USB controller to prevent the production of spurious interrupts
         periphs.USB_DEVICE.usbcon().reset();
 
+        let reset_cause = ResetCause::take(&periphs.CPU);
+
+        let mut sensorium = Sensorium::new(
+            periphs.ADC,
+            pins.pf5,
+            pins.pf4,
+            pins.pf1,
+            pins.pf0,
+            pins.pf6,
+        );
+        sensorium.set_time_constant(params.filter_time_constant);
+        if params.background_sample_hz > 0 {
+            sensorium.sample_in_background(&periphs.TC3, params.background_sample_hz);
+        }
+
         Self {
-            sensorium: Sensorium::new(periphs.ADC, pins.pf5, pins.pf4, pins.pf1, pins.pf0),
+            params,
+
+            sensorium,
 
-            compressor: Relay::new(pins.pd4.into_output(), 0, 120, 1),
+            compressor: CompressorDrive::Relay(
+                Relay::new(pins.pd4.into_output(), 0, 120, 1)
+                    .with_lockout(params.compressor_lockout),
+            ),
+            compressor_duty: DutyWindow::new(60_000),
+            energy: EnergyMeter::new(),
             heater: Relay::new(pins.pd5.into_output(), 60, 0, 1),
-            _relay2: Relay::new(pins.pd6.into_output(), 0, 0, 0),
+            relay2: Relay::new(pins.pd6.into_output(), 0, 0, 0),
             master_120vac: Relay::new(pins.pd7.into_output(), 0, 0, 0),
 
-            pwm: PWMController::new(periphs.TC1, pins.pb5, pins.pb6, pins.pb7, PWM_HZ),
+            pwm: ClimatePwm::new(PWMController::new(
+                periphs.TC1,
+                pins.pb5,
+                pins.pb6,
+                pins.pb7,
+                params.pwm_hz,
+            )),
 
             rtc: DS1307::new(I2c::new(
                 periphs.TWI,
@@ -576,24 +1096,45 @@ impl ClimateController {
                 pins.pd0.into_pull_up_input(),
                 50_000,
             )),
+            rtc_time: LastGood::new(params.rtc_retries),
             _sqw: pins.pc7,
 
+            eeprom: ConfigEeprom::new(periphs.EEPROM),
+
+            scheduler: {
+                let mut scheduler = Scheduler::new();
+                scheduler.add(
+                    Hours::from_bin(0),
+                    Minutes::from_bin(0),
+                    ScheduledAction::ResetExtremes,
+                );
+                scheduler
+            },
+            weekly: WeeklySchedule::new(),
+
             encoder: Encoder::new(pins.pe2, pins.pe6, pins.pb4, periphs.EXINT, periphs.TC4),
 
-            _pc6: pins.pc6,
-            _pf6: pins.pf6,
-            _pf7: pins.pf7,
+            buzzer: Buzzer::new(pins.pc6.into_output()),
 
-            display: Display::new(pins.pd2, pins.pd3, pins.pb0, pins.pb1, pins.pb2, pins.pb3),
+            display: Display::new(
+                pins.pd2, pins.pd3, pins.pb0, pins.pb1, pins.pb2, pins.pb3, pins.pf7,
+            )
+            .with_present(params.display_present),
 
             tc0: periphs.TC0,
+            cpu: periphs.CPU,
 
             next_sample: 0,
             next_update: 0,
+            update_interval: params.update_interval,
             next_display: 0,
             next_config: 0,
 
             target_temp: Target::Unset,
+            setpoint_pot: Potentiometer::new(
+                params.setpoint_pot_sens,
+                params.setpoint_pot_deadband,
+            ),
 
             config: ControllerConfig::DEFAULT,
             config_changed: false,
@@ -603,6 +1144,34 @@ impl ClimateController {
             ui_state: UIState::new(),
 
             last_condition: HabitatCondition::JustRight,
+            last_fault: None,
+            compressor_check: CompressorCheck::Idle,
+            over_temp: FaultLatch::new(
+                params.over_temp_margin,
+                params.over_temp_margin - params.over_temp_band,
+                params.over_temp_debounce,
+            ),
+            sensor_fault: FaultLatch::new(0.5, 0.5, params.sensor_fault_debounce),
+            placement_fault: FaultLatch::new(0.0, 0.0, params.placement_debounce),
+            spread_fault: FaultLatch::new(
+                params.placement_band,
+                params.placement_band,
+                params.placement_debounce,
+            ),
+            state: ControllerState::Init,
+            overrides: Overrides::NONE,
+            service_toggled: None,
+
+            last_input: 0,
+            humidity: None,
+            habitat_fan_on: false,
+            night: false,
+
+            max_loop_us: 0,
+
+            reset_cause,
+            previous_reset_cause: ResetCause::Unknown,
+            boot_count: 0,
         }
     }
 
@@ -612,76 +1181,581 @@ impl ClimateController {
 
         self.master_120vac.turn_on(0);
 
+        self.pwm
+            .set_coolant_pump_min_duty(self.params.pump_min_duty);
+        self.pwm
+            .set_habitat_fan_min_duty(self.params.habitat_fan_min_duty);
+        self.pwm
+            .set_habitat_fan_switching(self.params.habitat_fan_switching);
+        self.pwm
+            .set_condenser_fan_slew(self.params.condenser_fan_slew);
+        self.pwm.set_coolant_pump_slew(self.params.pump_slew);
+
+        // A clock holding garbage values can't be trusted even once corrected, so halt it until
+        // the time is set again. A recalculated weekday or a clamped date leaves the rest of the
+        // time intact, so the clock keeps running after those
+        if self
+            .rtc
+            .validate()
+            .is_ok_and(|report| !report.is_trustworthy())
+        {
+            let _ = self.rtc.halt_clock();
+        }
+
         self.load_config();
+        self.boot_count = self.eeprom.increment_boot_count();
+        self.previous_reset_cause = self.eeprom.swap_reset_cause(self.reset_cause);
+
+        let _ = self.rtc.sqw_enable_1hz();
 
         self.display.init();
+        self.display.splash();
+
+        if self.encoder.is_held() {
+            self.self_test();
+        }
 
         init_millis(&self.tc0);
+        self.cpu.smcr().write(|w| {
+            w.sm().idle();
+            w.se().set_bit()
+        });
+        self.state = ControllerState::Grace;
     }
 
-    fn load_config(&mut self) {
-        if let Ok(data) = self.rtc.get_ram() {
-            if let Ok(config) = ControllerConfig::from_data(data) {
-                self.config = config;
+    /// Idle the CPU until the next interrupt, if enabled by [`ControllerParams::idle_sleep`]
+    pub fn sleep(&self) {
+        if self.params.idle_sleep {
+            avr_device::asm::sleep();
+        }
+    }
+
+    /// Exercise every output and show the raw sensor readings, for bring-up and field diagnostics;
+    /// entered by holding the button at boot
+    ///
+    /// Blocks throughout: each relay is pulsed in turn, each PWM channel is ramped up and back
+    /// down, and every display cell is filled. The raw ADC samples are then shown until the button
+    /// is pressed
+    fn self_test(&mut self) {
+        let mut data = [0; PAGE_SIZE];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = b'0' + (i / COLS) as u8;
+        }
+        *self.display.back_mut() = PageData::new(data);
+        self.display.swap();
+        arduino_hal::delay_ms(2000);
+
+        self.self_test_step(b"Compressor  ");
+        self.compressor.force_on(&mut self.pwm);
+        arduino_hal::delay_ms(500);
+        self.compressor.force_off(millis(), &mut self.pwm);
+
+        self.self_test_step(b"Heater      ");
+        self.heater.force_on();
+        arduino_hal::delay_ms(500);
+        self.heater.force_off(millis());
+
+        self.self_test_step(b"Relay 2     ");
+        self.relay2.force_on();
+        arduino_hal::delay_ms(500);
+        self.relay2.force_off(millis());
+
+        for (channel, label) in [
+            (ClimatePwm::CONDENSER_FAN, b"Condenser   "),
+            (ClimatePwm::HABITAT_FAN, b"Habitat fan "),
+            (ClimatePwm::COOLANT_PUMP, b"Coolant pump"),
+        ] {
+            self.self_test_step(label);
+            for duty in (0..=256).step_by(8).chain((0..256).step_by(8).rev()) {
+                self.pwm.set_duty(channel, duty);
+                arduino_hal::delay_ms(20);
             }
         }
+
+        while self.encoder.was_pressed() {}
+        while !self.encoder.was_pressed() {
+            let [coolant, habitat, condenser] = self.sensorium.raw_samples();
+            crate::page!(rewrite self.display.back_mut();
+                write b"Coolant ADC:   "; uint coolant;
+                write b"Habitat ADC:   "; uint habitat;
+                write b"Condenser ADC: "; uint condenser;
+                write b"Press to continue";
+            );
+            self.display.swap();
+            arduino_hal::delay_ms(100);
+        }
+
+        crate::page!(rewrite self.display.back_mut(););
+        self.display.swap();
+    }
+
+    fn self_test_step(&mut self, label: &[u8; 12]) {
+        crate::page!(rewrite self.display.back_mut();
+            write b"Self test:"; next_line;
+            write 12 label;
+        );
+        self.display.swap();
+    }
+
+    /// Provide the habitat's relative humidity in percent from an external sensor, or `None` if it
+    /// is unknown
+    ///
+    /// While known, the coolant is never chilled to within
+    /// [`ControllerParams::dew_point_margin`] of the habitat's dew point, to keep condensation from
+    /// dripping into the enclosure
+    pub const fn set_humidity(&mut self, humidity: Option<f32>) {
+        self.humidity = humidity;
+    }
+
+    /// Returns the weekly setpoint program, which is empty until programmed
+    ///
+    /// While it has any entries, the program replaces the configured day/night cycle; see
+    /// [`ControllerConfig::calculate_target_with_program`]
+    pub const fn weekly_schedule_mut(&mut self) -> &mut WeeklySchedule<4> {
+        &mut self.weekly
+    }
+
+    /// Pin the compressor on or off regardless of the control logic, or return it to automatic
+    /// control with `None`
+    ///
+    /// Overrides are flagged on the display and keep the backlight on while any is active. They
+    /// are ignored in service mode and once a shutdown has begun, both of which clear them all
+    pub fn override_compressor(&mut self, now: u32, state: Option<bool>) {
+        if self.state.is_shut_down() || self.is_servicing() {
+            return;
+        }
+        match state {
+            Some(true) => self.compressor.force_on(&mut self.pwm),
+            Some(false) => self.compressor.force_off(now, &mut self.pwm),
+            None => {}
+        }
+        self.overrides.compressor = state;
+    }
+
+    /// Pin the heater on or off regardless of the control logic, or return it to automatic control
+    /// with `None`
+    ///
+    /// See [`Self::override_compressor`]
+    pub fn override_heater(&mut self, now: u32, state: Option<bool>) {
+        if self.state.is_shut_down() || self.is_servicing() {
+            return;
+        }
+        match state {
+            Some(true) => self.heater.force_on(),
+            Some(false) => self.heater.force_off(now),
+            None => {}
+        }
+        self.overrides.heater = state;
+    }
+
+    /// Pin a PWM channel at the given duty (out of 256) regardless of the control logic, or return
+    /// it to automatic control with `None`
+    ///
+    /// See [`Self::override_compressor`]
+    pub fn override_duty(&mut self, channel: PwmChannel, duty: Option<u16>) {
+        if self.state.is_shut_down() || self.is_servicing() {
+            return;
+        }
+        if let Some(duty) = duty {
+            self.pwm.set_duty(channel, duty);
+        }
+        self.overrides.duty[channel as usize] = duty;
+    }
+
+    /// Checks if any output is currently overridden
+    #[must_use]
+    pub const fn has_overrides(&self) -> bool {
+        self.overrides.is_any()
+    }
+
+    /// Percentage of the last 10 minutes the compressor has been on
+    ///
+    /// A well-sized system idles at a moderate duty; one that keeps climbing points to a developing
+    /// problem such as a fouled condenser or a refrigerant leak
+    #[must_use]
+    pub const fn compressor_duty(&self) -> u8 {
+        self.compressor_duty.percent()
+    }
+
+    /// Estimated watt-hours used by every load since startup or the last reset from the Energy
+    /// page, from the nominal powers in the [`ControllerConfig`]
+    #[must_use]
+    pub const fn energy_wh(&self) -> u32 {
+        self.energy.total_wh()
+    }
+
+    /// Nominal power and current duty of each metered load, in the order compressor, heater,
+    /// condenser fan, habitat fan, coolant pump
+    const fn energy_loads(&self) -> [(u16, u16); 5] {
+        [
+            (self.config.compressor_watts, self.compressor.duty()),
+            (self.config.heater_watts, self.heater.is_on() as u16 * 256),
+            (
+                self.config.condenser_fan_watts,
+                self.pwm.output_duty(ClimatePwm::CONDENSER_FAN),
+            ),
+            (
+                self.config.habitat_fan_watts,
+                self.pwm.output_duty(ClimatePwm::HABITAT_FAN),
+            ),
+            (
+                self.config.coolant_pump_watts,
+                self.pwm.output_duty(ClimatePwm::COOLANT_PUMP),
+            ),
+        ]
+    }
+
+    /// Longest time any single main loop iteration has taken, in microseconds
+    #[must_use]
+    pub const fn max_loop_us(&self) -> u32 {
+        self.max_loop_us
+    }
+
+    /// Begin a safe shutdown: stop the compressor and heater at once, keep the condenser fan and
+    /// coolant pump running for [`ControllerParams::shutdown_cooldown`] to dissipate head
+    /// pressure, then drop the master relay
+    ///
+    /// Regulation stops immediately and does not resume until reset
+    pub fn shutdown(&mut self, now: u32) {
+        if self.state.is_shut_down() {
+            return;
+        }
+
+        self.overrides = Overrides::NONE;
+        self.compressor.force_off(now, &mut self.pwm);
+        self.heater.force_off(now);
+
+        self.set_habitat_fan_duty(0);
+        self.pwm.set_condenser_fan(256);
+        self.set_coolant_pump_duty(256);
+
+        self.state = ControllerState::ShuttingDown(now);
+    }
+
+    /// Park every output for servicing: all relays, the master relay included, are switched off
+    /// and every PWM channel is stopped, while sampling and the display carry on
+    ///
+    /// Unlike a shutdown, this is undone by [`Self::exit_service`]. Has no effect once a shutdown
+    /// has begun
+    pub fn enter_service(&mut self, now: u32) {
+        if self.state.is_shut_down() || self.is_servicing() {
+            return;
+        }
+
+        self.overrides = Overrides::NONE;
+        self.compressor.force_off(now, &mut self.pwm);
+        self.heater.force_off(now);
+        self.relay2.force_off(now);
+        self.master_120vac.force_off(now);
+
+        self.pwm.set_condenser_fan(0);
+        self.pwm.set_habitat_fan(0);
+        self.pwm.set_coolant_pump(0);
+        self.habitat_fan_on = false;
+
+        self.alert(now, None);
+        self.state = ControllerState::Service;
+    }
+
+    /// Leave service mode, powering the master relay back up and resuming regulation through the
+    /// same settling stage as at startup
+    pub fn exit_service(&mut self, now: u32) {
+        if !self.is_servicing() {
+            return;
+        }
+
+        self.master_120vac.turn_on(now);
+        self.last_condition = HabitatCondition::JustRight;
+        self.state = ControllerState::Grace;
+    }
+
+    /// Checks if the outputs are parked for servicing
+    #[must_use]
+    pub const fn is_servicing(&self) -> bool {
+        matches!(self.state, ControllerState::Service)
+    }
+
+    /// Enter or leave service mode from the Service page, ignoring presses within
+    /// [`ControllerParams::service_guard`] of the last toggle
+    fn toggle_service(&mut self, now: u32) {
+        if self
+            .service_toggled
+            .is_some_and(|at| now.wrapping_sub(at) < self.params.service_guard)
+        {
+            return;
+        }
+        self.service_toggled = Some(now);
+
+        if self.is_servicing() {
+            self.exit_service(now);
+        } else {
+            self.enter_service(now);
+        }
+    }
+
+    /// Advance the control state machine by one step, running whatever the current state calls for
+    fn step(&mut self, now: u32) {
+        match self.state {
+            ControllerState::Init | ControllerState::Shutdown | ControllerState::Service => {}
+            ControllerState::Grace => {
+                if self.target_temp.value().is_some() {
+                    self.state = ControllerState::Running;
+                } else {
+                    // Circulate while calibrating so the first coolant reading is representative
+                    // of the loop
+                    self.set_coolant_pump_duty(self.params.warmup_pump_duty);
+                    self.set_habitat_fan_duty(self.params.warmup_fan_duty);
+                }
+            }
+            ControllerState::Running => {
+                let fault = self.update(now).err();
+                self.alert(now, fault);
+
+                // Running a compressor that isn't cooling only risks damaging it further
+                if fault == Some(ControllerError::CompressorFault) {
+                    self.shutdown(now);
+                }
+            }
+            ControllerState::ShuttingDown(since) => {
+                if now.wrapping_sub(since) >= self.params.shutdown_cooldown {
+                    self.pwm.set_condenser_fan(0);
+                    self.pwm.set_coolant_pump(0);
+                    self.master_120vac.force_off(now);
+                    self.state = ControllerState::Shutdown;
+                }
+            }
+        }
+
+        // Slewed channels ramp in every state that drives them, so the pump soft-starts while the
+        // sensors settle and during a shutdown's cooldown too
+        self.pwm.slew();
+    }
+
+    /// Load the configuration from the RTC RAM, falling back to the internal EEPROM if the RTC is
+    /// absent or holds no valid configuration
+    fn load_config(&mut self) {
+        let config = self.read_rtc_config().or_else(|_| {
+            ControllerConfig::from_data(self.eeprom.load_config())
+                .map_err(|_| ControllerError::InvalidConfig)
+        });
+
+        if let Ok(config) = config {
+            self.config = config;
+            self.apply_trims();
+        }
+    }
+
+    /// Apply the configured sensor trims, converted from fahrenheit
+    fn apply_trims(&mut self) {
+        const F_TO_C: f32 = 5.0 / 9.0;
+        self.sensorium
+            .coolant_temp_mut()
+            .set_offset(self.config.coolant_trim * F_TO_C);
+        self.sensorium
+            .habitat_temp_mut()
+            .set_offset(self.config.habitat_trim * F_TO_C);
+        self.sensorium
+            .condenser_temp_mut()
+            .set_offset(self.config.condenser_trim * F_TO_C);
+    }
+
+    fn read_rtc_config(&mut self) -> Result<ControllerConfig, ControllerError> {
+        let data = self.rtc.get_ram()?;
+        ControllerConfig::from_data(data).map_err(|_| ControllerError::InvalidConfig)
     }
 
     fn save_config(&mut self) {
         if self.config_changed {
-            let _ = self.rtc.set_ram(self.config.clone().into_data());
+            let data = self.config.clone().into_data();
+            let _ = self.rtc.set_ram(data);
+            self.eeprom.store_config(data);
             self.config_changed = false;
         }
     }
 
     fn set_condenser_fan_duty(&mut self, duty: u16) {
-        self.pwm.set_duty_a(duty);
+        self.pwm
+            .command_condenser_fan(self.overrides.duty(ClimatePwm::CONDENSER_FAN, duty));
     }
 
     fn set_habitat_fan_duty(&mut self, duty: u16) {
-        self.pwm.set_duty_b(duty);
+        self.pwm
+            .set_habitat_fan(self.overrides.duty(ClimatePwm::HABITAT_FAN, duty));
     }
 
     fn set_coolant_pump_duty(&mut self, duty: u16) {
-        self.pwm.set_duty_c(duty);
+        self.pwm
+            .command_coolant_pump(self.overrides.duty(ClimatePwm::COOLANT_PUMP, duty));
+    }
+
+    /// Switch the compressor on as the control logic demands, unless overridden; returns whether it
+    /// was switched on
+    fn compressor_on(&mut self, now: u32) -> bool {
+        self.overrides.compressor.is_none() && self.compressor.turn_on(now, &mut self.pwm)
+    }
+
+    /// Switch the compressor off as the control logic demands, unless overridden
+    fn compressor_off(&mut self, now: u32) {
+        if self.overrides.compressor.is_none() {
+            self.compressor.turn_off(now, &mut self.pwm);
+        }
+    }
+
+    /// Switch the heater on or off as the control logic demands, unless overridden
+    fn set_heater(&mut self, now: u32, on: bool) {
+        if self.overrides.heater.is_some() {
+            return;
+        }
+        if on {
+            self.heater.activate(now);
+        } else {
+            self.heater.deactivate(now);
+        }
+    }
+
+    /// Stop the compressor if the coolant is at risk of freezing
+    ///
+    /// This is a hard stop that neither an override, manual control nor a compressor still
+    /// awaiting verification (which the normal cut-out leaves running) can get past, so it is run
+    /// on every pass of the main loop whatever the control state
+    fn freeze_guard(&mut self, now: u32) {
+        if self.sensorium.coolant_temp().celsius() < self.params.freeze_threshold
+            && self.compressor.is_on()
+        {
+            self.overrides.compressor = None;
+            self.compressor.force_off(now, &mut self.pwm);
+            self.control_state.compressor = false;
+        }
+    }
+
+    /// Back off the update rate while nothing is near a threshold, returning to full rate as soon
+    /// as anything is
+    ///
+    /// The condenser fan, the slewed outputs and the compressor's capacity all step once per
+    /// update, so a running compressor or a hot condenser is never quiet either. NaN fails every
+    /// comparison, so an invalid habitat or coolant reading is never quiet, while an absent
+    /// condenser sensor doesn't hold the rate up
+    fn pace_updates(&mut self, habitat_delta: f32, coolant: f32, condenser: f32, cut_in: f32) {
+        let band = self.params.quiet_band;
+        let quiet = habitat_delta < band
+            && habitat_delta > -band
+            && coolant < cut_in - band
+            && coolant > cut_in - CUT_OUT_BAND + band
+            && !self.compressor.is_on()
+            && (condenser < CONDENSER_HOT || !is_finite(condenser));
+
+        self.update_interval = if quiet {
+            (self.update_interval * 2).min(self.params.max_update_interval)
+        } else {
+            self.params.update_interval
+        };
+    }
+
+    /// Coolant temperature above which the compressor cuts in, given the target, both in degrees
+    /// celsius
+    ///
+    /// The configured coolant target and subcooling are in degrees fahrenheit like the rest of the
+    /// configuration, so are converted here
+    const fn coolant_cut_in(&self, target: f32) -> f32 {
+        if self.config.coolant_target > 0.0 {
+            Temperature::from_f(self.config.coolant_target).as_c()
+        } else {
+            target - self.config.min_effective_subcooling * (5.0 / 9.0)
+        }
     }
 
     const fn tune_subcooling(&mut self, delta: f32) {
+        // The subcooling is only in effect while the coolant tracks the habitat target
+        if self.config.coolant_target > 0.0 {
+            return;
+        }
         self.config.min_effective_subcooling += delta;
         self.config_changed = true;
     }
 
+    /// Announce a change in fault condition on the buzzer
+    fn alert(&mut self, now: u32, fault: Option<ControllerError>) {
+        if fault == self.last_fault {
+            return;
+        }
+
+        match fault {
+            None => self.buzzer.silence(),
+            Some(ControllerError::OverTemp) => self.buzzer.beep_repeating(now, 250, 750),
+            Some(ControllerError::SensorFault) => self.buzzer.alarm(),
+            Some(ControllerError::CompressorFault) => self.buzzer.beep_repeating(now, 1000, 1000),
+            Some(
+                ControllerError::I2c(_)
+                | ControllerError::InvalidConfig
+                | ControllerError::SensorPlacement,
+            ) => {
+                self.buzzer.beep(now, 100, 100, 3);
+            }
+        }
+        self.last_fault = fault;
+    }
+
+    /// Run the control logic, returning any fault condition detected
+    ///
+    /// # Errors
+    /// Returns [`ControllerError::SensorFault`] if the habitat or coolant sensor has failed,
+    /// [`ControllerError::OverTemp`] if the habitat has been more than
+    /// [`ControllerParams::over_temp_margin`] above target (both debounced, with hysteresis),
+    /// [`ControllerError::CompressorFault`] if the coolant did not respond to the compressor within
+    /// [`ControllerParams::compressor_response_timeout`], or [`ControllerError::SensorPlacement`]
+    /// if the readings suggest swapped sensors
     #[inline(never)]
-    fn update(&mut self, now: u32) {
+    fn update(&mut self, now: u32) -> Result<(), ControllerError> {
         let Some(target) = self.target_temp.value() else {
-            return;
+            self.state = ControllerState::Grace;
+            return Ok(());
         };
 
-        let habitat = self.sensorium.habitat_temp().fahrenheit();
-        let coolant = self.sensorium.coolant_temp().fahrenheit();
-        let condenser = self.sensorium.condenser_temp().fahrenheit();
+        let Readings {
+            coolant,
+            habitat,
+            condenser,
+        } = if self.params.average_samples {
+            self.sensorium.take_mean_readings()
+        } else {
+            self.sensorium.readings()
+        };
 
-        let new_condition = self
-            .last_condition
-            .next_toward(HabitatCondition::test(habitat, target));
+        // All thresholds below are in degrees celsius; the target is converted from the unit it is
+        // configured and displayed in
+        let target = Temperature::from_f(target).as_c();
+        let (coolant, habitat, condenser) = (coolant.as_c(), habitat.as_c(), condenser.as_c());
+
+        // Without a humidity reading this always holds, so installations without the sensor
+        // behave as before
+        let above_dew_point = self
+            .humidity
+            .is_none_or(|rh| coolant > dew_point(habitat, rh) + self.params.dew_point_margin);
+
+        let freezing = coolant < self.params.freeze_threshold;
+        let may_chill = above_dew_point && !freezing;
+
+        // NaN fails every threshold comparison and would be classified as too hot, so hold the last
+        // condition while the habitat reading is invalid
+        let new_condition = if is_finite(habitat) {
+            self.last_condition
+                .next_toward(HabitatCondition::test(habitat, target))
+        } else {
+            self.last_condition
+        };
         if new_condition.is_different(self.last_condition) {
             let mut defer = false;
 
             match new_condition {
                 HabitatCondition::TooCold => {
-                    self.heater.turn_on(now);
-                }
-                HabitatCondition::Cool => {
-                    self.set_habitat_fan_duty(0);
+                    self.set_heater(now, true);
                 }
                 HabitatCondition::JustRight => {
-                    self.heater.turn_off(now);
-                }
-                HabitatCondition::Warm => {
-                    self.set_habitat_fan_duty(256);
+                    self.set_heater(now, false);
                 }
+                HabitatCondition::Cool | HabitatCondition::Warm => {}
                 HabitatCondition::TooHot => {
-                    if self.compressor.turn_on(now) {
+                    if may_chill && self.compressor_on(now) {
                         self.tune_subcooling(0.5);
                     } else {
                         defer = true;
@@ -694,111 +1768,323 @@ impl ClimateController {
             }
         }
 
+        // Project the coolant along its slope so the thresholds act on where it is headed. An
+        // unknown slope (e.g. while the sensor settles) leaves the measured temperature as is
+        let slope = self.sensorium.coolant_temp().slope();
+        let effective_coolant = if is_finite(slope) {
+            coolant + self.params.coolant_anticipation * slope * self.params.coolant_lookahead
+        } else {
+            coolant
+        };
+
         let subcooling = target - coolant;
-        if subcooling < self.config.min_effective_subcooling {
-            if self.compressor.turn_on(now) {
+        let cut_in = self.coolant_cut_in(target);
+        if effective_coolant > cut_in && may_chill {
+            if self.compressor_on(now) {
                 self.tune_subcooling(-0.1);
             }
-        } else if subcooling > self.config.min_effective_subcooling + 10.0 {
-            self.compressor.turn_off(now);
+        } else if effective_coolant < cut_in - CUT_OUT_BAND || !may_chill {
+            self.compressor_off(now);
+        }
+
+        // Habitat fan: on above the deadband, off below it, and held in between so it doesn't
+        // chatter about the target. NaN fails both comparisons, holding the fan as it was
+        let habitat_delta = habitat - target;
+        if habitat_delta > self.params.habitat_fan_deadband {
+            self.habitat_fan_on = true;
+        } else if habitat_delta < -self.params.habitat_fan_deadband {
+            self.habitat_fan_on = false;
+        }
+
+        self.pace_updates(habitat_delta, effective_coolant, condenser, cut_in);
+
+        // The fan only cools the habitat if the coolant is colder than it. While the compressor is
+        // locked out, coolant that is no colder than the target would only warm the habitat, so
+        // the fan is gated off (without forgetting its state) until cooling is available again;
+        // once the compressor is running or allowed to start, the fan follows the deadband alone
+        let cooling_available =
+            self.compressor.is_on() || self.compressor.can_activate(now) || subcooling > 0.0;
+        self.set_habitat_fan_duty(if self.habitat_fan_on && cooling_available {
+            256
+        } else {
+            0
+        });
+
+        // A modulating drive backs off toward quarter capacity as the coolant approaches the
+        // cut-out point rather than running flat out until it cycles
+        let excess = (cut_in - coolant).clamp(0.0, CUT_OUT_BAND);
+        if self.overrides.compressor.is_none() {
+            self.compressor
+                .set_capacity(&mut self.pwm, 256 - (excess * 34.56) as u16);
         }
 
         // Fail-safe: max out the condenser fan in case of condenser temp sensor failure to
         // avoid overheating the compressor
-        self.set_condenser_fan_duty(if is_finite(condenser) {
-            if condenser < 80.0 {
-                0
-            } else if condenser <= 90.0 {
-                ((condenser - 80.0) * 25.6) as u16
-            } else {
-                256
-            }
+        let condenser_duty = if is_finite(condenser) {
+            remap(condenser, CONDENSER_HOT, CONDENSER_FULL, 0.0, 256.0) as u16
         } else {
             256
+        };
+
+        // Keep air moving over the condenser for a while after the compressor stops. The relay
+        // counts as switched off at startup, so this also briefly runs after power-up
+        let post_run = self
+            .compressor
+            .off_since()
+            .is_some_and(|since| now.wrapping_sub(since) < self.params.condenser_post_run);
+        self.set_condenser_fan_duty(if post_run {
+            condenser_duty.max(self.params.condenser_post_run_duty)
+        } else {
+            condenser_duty
         });
 
         self.set_coolant_pump_duty(if self.compressor.is_on() {
             256
-        } else if self.pwm.duty_b() > 0 {
-            192
+        } else if self.pwm.habitat_fan() > 0 {
+            // Remapped above the default `pump_min_duty` of 80, this drives the pump at 192
+            163
         } else {
             0
         });
 
+        self.compressor_check =
+            self.compressor_check
+                .next(&self.params, now, self.compressor.is_on(), coolant);
+        self.compressor_duty.update(now, self.compressor.is_on());
+
         // Verify that the compressor has in fact switched on by checking if the condenser is hot
         self.compressor
-            .verify_when_ready(now, || condenser >= 80.0, || true);
+            .verify_when_ready(now, || condenser >= CONDENSER_HOT, || true);
         self.compressor.restore_when_ready(now);
 
         // Verfy that the heater is not stuck on when switched off by checking if the target was
         // significantly overshot
-        self.heater.verify_when_ready(
-            now,
-            || true,
-            || {
-                self.target_temp
-                    .value()
-                    .is_some_and(|target| habitat < target + 1.0)
+        self.heater
+            .verify_when_ready(now, || true, || habitat < target + 0.555_556);
+        self.heater.tick(now);
+
+        self.check_faults(now, target, coolant, habitat, condenser)
+    }
+
+    /// Update the debounced fault conditions with the latest readings (in degrees celsius),
+    /// returning the most severe one active
+    fn check_faults(
+        &mut self,
+        now: u32,
+        target: f32,
+        coolant: f32,
+        habitat: f32,
+        condenser: f32,
+    ) -> Result<(), ControllerError> {
+        let sensor_fault = self
+            .sensor_fault
+            .update(if is_finite(habitat) && is_finite(coolant) {
+                0.0
+            } else {
+                1.0
+            });
+        let over_temp = self.over_temp.update(habitat - target);
+        let misplaced = self.check_placement(now, coolant, condenser);
+
+        if sensor_fault {
+            Err(ControllerError::SensorFault)
+        } else if over_temp {
+            Err(ControllerError::OverTemp)
+        } else if matches!(self.compressor_check, CompressorCheck::Stalled) {
+            Err(ControllerError::CompressorFault)
+        } else if misplaced {
+            Err(ControllerError::SensorPlacement)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the readings for signs of swapped sensors, returning whether any were found
+    ///
+    /// Readings spread too far apart while everything should be at ambient, or a coolant reading
+    /// warmer than the condenser once the compressor is cooling (when the condenser is the hottest
+    /// point in the system and the coolant the coldest), are both hard to explain with the sensors
+    /// where they belong
+    ///
+    /// The spread is only checked once the compressor has been off for `placement_settle`, counted
+    /// from startup if it hasn't run since, so a cycle running up to a warm reboot has time to
+    /// wear off. The fault clears if the readings later settle back within the band
+    fn check_placement(&mut self, now: u32, coolant: f32, condenser: f32) -> bool {
+        let settled = self
+            .compressor
+            .off_since()
+            .is_some_and(|since| now.wrapping_sub(since) >= self.params.placement_settle);
+
+        // NaN holds the latch as it was while the readings may not have settled
+        let spread_out = self.params.placement_band > 0.0
+            && self.spread_fault.update(if settled {
+                self.sensorium.readings().spread().unwrap_or(f32::NAN)
+            } else {
+                f32::NAN
+            });
+
+        // NaN holds the latch as it was while the compressor isn't cooling
+        let inverted = self.placement_fault.update(
+            if matches!(self.compressor_check, CompressorCheck::Cooling) {
+                coolant - condenser
+            } else {
+                f32::NAN
             },
         );
-        self.heater.restore_when_ready(now);
+
+        spread_out || inverted
+    }
+
+    /// Read the clock, riding out sporadic bus errors with the last good reading
+    fn running_time(&mut self) -> I2cResult<Option<RTCTime>> {
+        self.rtc_time.update(self.rtc.get_running_time())
     }
 
     fn config(&mut self) {
         match self.target_temp {
             Target::Unset => {
-                self.target_temp = if let Ok(time) = self.rtc.get_time() {
-                    Target::Dynamic(self.config.calculate_target(time))
+                self.target_temp = if let Ok(Some(time)) = self.running_time() {
+                    Target::Dynamic(self.calculate_target(time))
                 } else {
-                    // If the RTC does not respond, fail-safe by holding the current habitat temperature
+                    // If the RTC does not respond or is not keeping time, fail-safe by holding the
+                    // current habitat temperature
                     Target::Static(self.sensorium.habitat_temp().fahrenheit())
                 }
             }
             Target::Static(_) => {
-                if let Ok(time) = self.rtc.get_time() {
+                if let Ok(Some(time)) = self.running_time() {
                     self.load_config();
-                    self.target_temp = Target::Dynamic(self.config.calculate_target(time));
+                    self.target_temp = Target::Dynamic(self.calculate_target(time));
                 }
             }
             Target::Dynamic(_) => {
-                if let Ok(time) = self.rtc.get_time() {
-                    self.target_temp = Target::Dynamic(self.config.calculate_target(time));
+                if let Ok(Some(time)) = self.running_time() {
+                    self.target_temp = Target::Dynamic(self.calculate_target(time));
+                    self.night = !(6..18).contains(&time.hours.bin());
+                    self.run_schedule(time);
                 }
             }
         }
 
+        // A setpoint knob stands in for the schedule, leaving the RTC handling above as it is
+        if let Some(setpoint) = self.sample_setpoint_pot() {
+            self.target_temp = match self.target_temp {
+                Target::Dynamic(_) => Target::Dynamic(setpoint),
+                Target::Unset | Target::Static(_) => Target::Static(setpoint),
+            };
+        }
+
         self.save_config();
     }
 
+    /// Sample the setpoint potentiometer on the configured aux input, returning the target in
+    /// degrees fahrenheit it is set to, or `None` if no potentiometer is configured
+    fn sample_setpoint_pot(&mut self) -> Option<f32> {
+        let input = match self.config.setpoint_pot {
+            1 => AuxInput::A1,
+            5 => AuxInput::A5,
+            _ => return None,
+        };
+        self.setpoint_pot.sample(self.sensorium.aux_read(input));
+
+        Some(remap(
+            self.setpoint_pot.position(),
+            0.0,
+            1023.0,
+            f32::from(self.config.setpoint_pot_min),
+            f32::from(self.config.setpoint_pot_max),
+        ))
+    }
+
+    const fn calculate_target(&self, time: RTCTime) -> f32 {
+        self.config
+            .calculate_target_with_program(time, self.weekly.setpoint(time))
+    }
+
+    const fn run_schedule(&mut self, time: RTCTime) {
+        while let Some(action) = self.scheduler.poll(time) {
+            match action {
+                ScheduledAction::ResetExtremes => self.sensorium.reset_extremes(),
+            }
+        }
+    }
+
+    const fn diagnostics(&self) -> Diagnostics {
+        Diagnostics {
+            adc: self.sensorium.filtered_samples(),
+            duty_pct: [
+                self.pwm.output_duty(ClimatePwm::CONDENSER_FAN) * 100 / 256,
+                self.pwm.output_duty(ClimatePwm::HABITAT_FAN) * 100 / 256,
+                self.pwm.output_duty(ClimatePwm::COOLANT_PUMP) * 100 / 256,
+            ],
+            relays: [
+                self.compressor.is_on(),
+                self.heater.is_on(),
+                self.relay2.is_on(),
+                self.master_120vac.is_on(),
+            ],
+            max_loop_us: if self.max_loop_us > u16::MAX as u32 {
+                u16::MAX
+            } else {
+                self.max_loop_us as u16
+            },
+            compressor_pct: self.compressor_duty.percent(),
+        }
+    }
+
     #[inline(never)]
     fn display(&mut self) {
+        let diag = self.diagnostics();
         match self.ui_state.mode() {
             UIMode::Normal(page) => crate::page!(
                 rewrite self.display.back_mut();
                 match PAGE (*page) {
                     PageId::TimeAndTarget => {
-                        if RTC (let Ok(time) = self.rtc.get_time()) {
-                            write 3 time.day.abbrev();
-                            skip 1;
-                            write b"20";
-                            hexit2 time.year.bcd();
-                            byte b'.';
-                            hexit2 time.month.bcd();
-                            byte b'.';
-                            hexit2 time.date.bcd();
-                            end_line;
-                            hexit2 time.hours.bcd_24h();
-                            byte b':';
-                            hexit2 time.minutes.bcd();
-                            byte b':';
-                            hexit2 time.seconds.bcd();
-                            skip 1;
-                            write 11 self.config.diapause_status(time);
-                            end_line;
-                        } else {
-                            write b"RTC not responding";
-                            end_line;
-                            next_line;
+                        match RTC (self.rtc_time.update(self.rtc.get_running_time())) {
+                            Ok(Some(time)) => {
+                                write 3 time.day.abbrev();
+                                skip 1;
+                                write b"20";
+                                hexit2 time.year.bcd();
+                                byte b'.';
+                                hexit2 time.month.bcd();
+                                byte b'.';
+                                hexit2 time.date.bcd();
+                                if OVERRIDE (self.overrides.is_any()) {
+                                    write b" [OVR]";
+                                } else {
+                                    skip 6;
+                                }
+                                if CLOCK_12H (self.config.clock_12h) {
+                                    hexit2 time.hours.bcd_12h() & 0x1f;
+                                    byte b':';
+                                    hexit2 time.minutes.bcd();
+                                    skip 1;
+                                    if PM (time.hours.bin() >= 12) {
+                                        write b"PM";
+                                    } else {
+                                        write b"AM";
+                                    }
+                                } else {
+                                    hexit2 time.hours.bcd_24h();
+                                    byte b':';
+                                    hexit2 time.minutes.bcd();
+                                    byte b':';
+                                    hexit2 time.seconds.bcd();
+                                }
+                                skip 1;
+                                write 11 self.config.diapause_status(time);
+                                end_line;
+                            }
+                            Ok(None) => {
+                                write b"RTC halted; set time";
+                                next_line;
+                            }
+                            Err(_) => {
+                                write b"RTC not responding";
+                                end_line;
+                                next_line;
+                            }
                         }
                         if TARGET (let Some(target) = self.target_temp.value()) {
                             write b"Setpoint:   ";
@@ -809,21 +2095,123 @@ impl ClimateController {
                             end_line;
                         }
                         write b"Habitat:    ";
-                        decimal self.sensorium.habitat_temp().fahrenheit();
+                        decimal self.sensorium.habitat_temp().fahrenheit(),
+                            self.params.display_quantum;
                         byte b'F';
                     }
                     PageId::TempReadings => {
-                        write b"Habitat:    ";
-                        decimal self.sensorium.habitat_temp().fahrenheit();
+                        write b"Habitat:   ";
+                        byte trend_arrow(
+                            self.sensorium.habitat_temp().slope_fahrenheit(),
+                            self.params.trend_threshold,
+                        );
+                        decimal self.sensorium.habitat_temp().fahrenheit(),
+                            self.params.display_quantum;
                         byte b'F';
-                        write b"Coolant:    ";
-                        decimal self.sensorium.coolant_temp().fahrenheit();
+                        write b"Coolant:   ";
+                        byte trend_arrow(
+                            self.sensorium.coolant_temp().slope_fahrenheit(),
+                            self.params.trend_threshold,
+                        );
+                        decimal self.sensorium.coolant_temp().fahrenheit(),
+                            self.params.display_quantum;
                         byte b'F';
-                        write b"Condenser:  ";
-                        decimal self.sensorium.condenser_temp().fahrenheit();
+                        write b"Condenser: ";
+                        byte trend_arrow(
+                            self.sensorium.condenser_temp().slope_fahrenheit(),
+                            self.params.trend_threshold,
+                        );
+                        decimal self.sensorium.condenser_temp().fahrenheit(),
+                            self.params.display_quantum;
                         byte b'F';
+                        write b"Coolant/s:  ";
+                        decimal self.sensorium.coolant_temp().slope_fahrenheit();
+                        byte b'F';
+                    }
+                    PageId::Extremes => {
+                        write b"Habitat Lo: ";
+                        decimal self.sensorium.habitat_temp().min_fahrenheit(),
+                            self.params.display_quantum;
+                        byte b'F';
+                        write b"Habitat Hi: ";
+                        decimal self.sensorium.habitat_temp().max_fahrenheit(),
+                            self.params.display_quantum;
+                        byte b'F';
+                        write b"Coolant Lo: ";
+                        decimal self.sensorium.coolant_temp().min_fahrenheit(),
+                            self.params.display_quantum;
+                        byte b'F';
+                        write b"Coolant Hi: ";
+                        decimal self.sensorium.coolant_temp().max_fahrenheit(),
+                            self.params.display_quantum;
+                        byte b'F';
+                    }
+                    PageId::Diagnostics => {
+                        write b"ADC:";
+                        uint diag.adc[0];
+                        uint diag.adc[1];
+                        uint diag.adc[2];
+                        end_line;
+                        write b"PWM%:";
+                        uint diag.duty_pct[0];
+                        uint diag.duty_pct[1];
+                        uint diag.duty_pct[2];
+                        write b"Cmp:";
+                        byte b'0' + diag.relays[0] as u8;
+                        write b" Htr:";
+                        byte b'0' + diag.relays[1] as u8;
+                        write b" R2:";
+                        byte b'0' + diag.relays[2] as u8;
+                        write b" M:";
+                        byte b'0' + diag.relays[3] as u8;
+                        write b"Loop us:";
+                        uint diag.max_loop_us;
+                        write b" C%";
+                        write 4 &crate::utils::u16_to_bytes(diag.compressor_pct as u16)[1..];
+                    }
+                    PageId::Resets => {
+                        write b"Boot count:    ";
+                        uint self.boot_count;
+                        write b"Last reset:";
+                        write 9 self.reset_cause.name();
+                        write b"Prev reset:";
+                        write 9 self.previous_reset_cause.name();
+                        end_page;
+                    }
+                    PageId::Energy => {
+                        write b"Energy kWh:  ";
+                        decimal kwh(self.energy.total_wh());
+                        write b"Cmp";
+                        decimal kwh(self.energy.wh(0));
+                        write b"Htr";
+                        decimal kwh(self.energy.wh(1));
+                        write b"Cnd";
+                        decimal kwh(self.energy.wh(2));
+                        write b"Hab";
+                        decimal kwh(self.energy.wh(3));
+                        write b"Pmp";
+                        decimal kwh(self.energy.wh(4));
                         end_page;
                     }
+                    PageId::Service => {
+                        if SERVICE (matches!(self.state, ControllerState::Service)) {
+                            write b"    SERVICE MODE    ";
+                            write b"Habitat:    ";
+                            decimal self.sensorium.habitat_temp().fahrenheit(),
+                                self.params.display_quantum;
+                            byte b'F';
+                            write b"Coolant:    ";
+                            decimal self.sensorium.coolant_temp().fahrenheit(),
+                                self.params.display_quantum;
+                            byte b'F';
+                            write b"> [Press To Resume]";
+                            end_page;
+                        } else {
+                            write b"> [Press To Service]";
+                            write b"  ...";
+                            end_page;
+                        }
+                    }
                     PageId::Configuration => {
                         write b"> [Press To Config] ";
                         write b"  ...";
@@ -852,73 +2240,107 @@ impl ClimateController {
     }
 
     fn periodic(&mut self) {
+        let start = micros();
         let now = millis();
 
-        if now >= self.next_sample {
-            self.sensorium.sample();
-            self.next_sample += SAMPLE_INTERVAL;
+        if self.params.background_sample_hz > 0 {
+            self.sensorium.collect();
+        } else if now >= self.next_sample {
+            self.sensorium.sample(now);
+            self.next_sample += self.params.sample_interval;
         }
 
+        self.freeze_guard(now);
+
         if !self.ui_state.is_in_manual_mode() && now >= self.next_update {
-            self.update(now);
-            self.next_update += UPDATE_INTERVAL;
+            self.step(now);
+            self.next_update += self.update_interval;
         }
 
-        if now >= CALIBRATION_PERIOD && now >= self.next_config {
+        if now >= self.params.calibration_period && now >= self.next_config {
             self.config();
-            self.next_config += CONFIG_INTERVAL;
+            self.energy.update(now, self.energy_loads());
+            self.next_config += self.params.config_interval;
         }
 
+        self.buzzer.tick(now);
+
         if now >= self.next_display {
             self.display();
-            self.next_display += DISPLAY_INTERVAL;
+            self.next_display += self.params.display_interval;
         }
 
         if let Some(click) = self.encoder.next_click() {
-            if let Some(buf) = self.ui_state.handle_click(click) {
+            self.last_input = now;
+            // Live control would otherwise switch outputs that are meant to stay parked
+            if let Some(buf) = self
+                .ui_state
+                .handle_click(click)
+                .filter(|_| !self.is_servicing())
+            {
                 match buf {
                     ControlBuffer::Compressor(value) => {
                         if value {
-                            self.compressor.force_on();
+                            self.compressor.force_on(&mut self.pwm);
                         } else {
-                            self.compressor.force_off();
+                            self.compressor.force_off(now, &mut self.pwm);
                         }
                     }
                     ControlBuffer::Heater(value) => {
                         if value {
                             self.heater.force_on();
                         } else {
-                            self.heater.force_off();
+                            self.heater.force_off(now);
                         }
                     }
-                    ControlBuffer::CondenserFan(value) => self.pwm.set_duty_a(value.0),
-                    ControlBuffer::HabitatFan(value) => self.pwm.set_duty_b(value.0),
-                    ControlBuffer::CoolantPump(value) => self.pwm.set_duty_c(value.0),
+                    ControlBuffer::CondenserFan(value) => self.pwm.set_condenser_fan(value.0),
+                    ControlBuffer::HabitatFan(value) => self.pwm.set_habitat_fan(value.0),
+                    ControlBuffer::CoolantPump(value) => self.pwm.set_coolant_pump(value.0),
                 }
                 self.control_state.set_buffer(buf);
             }
         }
 
         if self.encoder.was_pressed() {
-            let entering_manual;
-            (self.config_changed, entering_manual) = self
+            self.last_input = now;
+            match self
                 .ui_state
-                .handle_press(&mut self.config, &self.control_state);
-            if entering_manual {
-                self.control_state.compressor = self.compressor.is_on();
-                self.control_state.heater = self.heater.is_on();
-                self.control_state.duty_a = Duty(self.pwm.duty_a());
-                self.control_state.duty_b = Duty(self.pwm.duty_b());
-                self.control_state.duty_c = Duty(self.pwm.duty_c());
+                .handle_press(&mut self.config, &self.control_state)
+            {
+                PressAction::None => {}
+                PressAction::ConfigChanged => {
+                    self.config_changed = true;
+                    self.apply_trims();
+                }
+                PressAction::EnterManual => {
+                    self.control_state.compressor = self.compressor.is_on();
+                    self.control_state.heater = self.heater.is_on();
+                    self.control_state.duty_a = Duty(self.pwm.condenser_fan());
+                    self.control_state.duty_b = Duty(self.pwm.habitat_fan());
+                    self.control_state.duty_c = Duty(self.pwm.coolant_pump());
+                }
+                PressAction::ResetExtremes => self.sensorium.reset_extremes(),
+                PressAction::ResetEnergy => self.energy.reset(),
+                PressAction::Resettle => self.sensorium.resettle(),
+                PressAction::ToggleService => self.toggle_service(now),
             }
         }
+
+        // Keep the colony's nights dark unless someone is using the controller
+        self.display.set_backlight(
+            !self.night
+                || self.overrides.is_any()
+                || now.wrapping_sub(self.last_input) < self.params.backlight_timeout,
+        );
+
+        self.max_loop_us = self.max_loop_us.max(micros().wrapping_sub(start));
     }
 }
 
 #[entry]
 fn main() -> ! {
     let periphs = Peripherals::take().unwrap();
-    let mut controller = ClimateController::new(periphs);
+    let mut controller = ClimateController::new(periphs, ControllerParams::default());
 
     // Safety: not called inside avr_device::interrupt::free
     unsafe { avr_device::interrupt::enable() };
@@ -927,5 +2349,6 @@ fn main() -> ! {
 
     loop {
         controller.periodic();
+        controller.sleep();
     }
 }
@@ -1,4 +1,4 @@
-//! Faithful implementation of Arduino `millis()`
+//! Faithful implementation of Arduino `millis()` and `micros()`
 
 use core::cell::Cell;
 
@@ -8,6 +8,7 @@ use avr_device::interrupt::Mutex;
 const MICROS_PER_SECOND: u32 = 1_000_000;
 const CYCLES_PER_MICRO: u32 = DefaultClock::FREQ / MICROS_PER_SECOND;
 const MICROS_PER_OVF: u32 = 64 * 256 / CYCLES_PER_MICRO;
+const MICROS_PER_TICK: u32 = MICROS_PER_OVF / 256;
 
 const MILLIS_INC: u32 = MICROS_PER_OVF / 1000;
 const FRACT_INC: u32 = MICROS_PER_OVF.rem_euclid(1000) >> 3;
@@ -50,8 +51,21 @@ pub fn init_millis(tc0: &TC0) {
     tc0.tccr0b().write(|w| w.cs0().prescale_64());
     tc0.timsk0().write(|w| w.toie0().set_bit());
 
+    reset_millis();
+}
+
+/// Restart the millisecond count from zero
+///
+/// Note: anything holding timestamps or deadlines taken from [`millis`] before the reset, such as
+/// the [`ClimateController`]'s update schedule, must recompute them, since times before the reset
+/// will appear to be far in the future
+///
+/// [`ClimateController`]: crate::ClimateController
+pub fn reset_millis() {
     avr_device::interrupt::free(|cs| {
         MILLIS.borrow(cs).set(0);
+        FRACT.borrow(cs).set(0);
+        OVERFLOWS.borrow(cs).set(0);
     });
 }
 
@@ -60,3 +74,78 @@ pub fn init_millis(tc0: &TC0) {
 pub fn millis() -> u32 {
     avr_device::interrupt::free(|cs| MILLIS.borrow(cs).get())
 }
+
+/// Microseconds since last reset, in steps of 4 µs
+///
+/// Note: this wraps after ~71.6 minutes, so it is only suited to timing short intervals
+#[must_use]
+pub fn micros() -> u32 {
+    // Safety: Timer0's count and flag registers are only read here, never written
+    let tc0 = unsafe { TC0::steal() };
+    avr_device::interrupt::free(|cs| {
+        let mut overflows = OVERFLOWS.borrow(cs).get();
+        let ticks = tc0.tcnt0().read().bits();
+
+        // An overflow since interrupts were disabled is pending rather than counted; a count of 255
+        // means it happened just after the count was read
+        if tc0.tifr0().read().tov0().bit_is_set() && ticks < 255 {
+            overflows = overflows.wrapping_add(1);
+        }
+
+        ((overflows << 8) | ticks as u32).wrapping_mul(MICROS_PER_TICK)
+    })
+}
+
+/// Whole seconds since last reset
+///
+/// Note: like [`millis`], this wraps after ~49.7 days
+#[must_use]
+pub fn seconds() -> u32 {
+    millis() / 1000
+}
+
+/// Time since last reset, broken down into days, hours, minutes and seconds
+///
+/// Note: like [`millis`], this wraps after ~49.7 days
+pub fn uptime() -> Uptime {
+    Uptime::from_seconds(seconds())
+}
+
+/// A duration broken down into days, hours, minutes and seconds
+///
+/// Use [`duration_to_bytes`] to display the total
+///
+/// [`duration_to_bytes`]: crate::utils::duration_to_bytes
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct Uptime {
+    /// Whole days
+    pub days: u16,
+    /// Hours past the last whole day, `0..24`
+    pub hours: u8,
+    /// Minutes past the last whole hour, `0..60`
+    pub minutes: u8,
+    /// Seconds past the last whole minute, `0..60`
+    pub seconds: u8,
+}
+
+impl Uptime {
+    /// Break down a number of seconds
+    pub const fn from_seconds(secs: u32) -> Self {
+        let minutes = secs / 60;
+        let hours = minutes / 60;
+        Self {
+            days: (hours / 24) as u16,
+            hours: (hours % 24) as u8,
+            minutes: (minutes % 60) as u8,
+            seconds: (secs % 60) as u8,
+        }
+    }
+
+    /// Total number of seconds
+    #[must_use]
+    pub const fn total_seconds(self) -> u32 {
+        ((self.days as u32 * 24 + self.hours as u32) * 60 + self.minutes as u32) * 60
+            + self.seconds as u32
+    }
+}
@@ -0,0 +1,84 @@
+//! Classification of what caused the last reset of the microcontroller
+
+use arduino_hal::pac::CPU;
+
+/// Cause of the last reset, as recorded by the MCU status register (`MCUSR`)
+///
+/// Note: the Caterina bootloader most boards ship with runs first and clears `MCUSR` before
+/// starting the sketch, so behind it the cause almost always reads as [`Unknown`](Self::Unknown).
+/// Only boards flashed without a bootloader report it reliably
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ResetCause {
+    /// Supply voltage came up from below the power-on threshold
+    PowerOn,
+
+    /// Supply voltage sagged below the brown-out detection level
+    Brownout,
+
+    /// Watchdog timer expired
+    Watchdog,
+
+    /// `RESET` pin was pulled low
+    External,
+
+    /// USB host requested a reset
+    Usb,
+
+    /// No flag was set, e.g. after a jump to the reset vector, or because a bootloader cleared them
+    Unknown,
+}
+
+impl ResetCause {
+    /// Read the reset flags, then clear them
+    ///
+    /// The flags accumulate across resets until cleared, so this must be called once, early in
+    /// boot, for the next reset to be told apart from this one. A power-on reset may also set the
+    /// brown-out flag as the supply rises, so power-on takes precedence over every other cause
+    #[must_use]
+    pub fn take(cpu: &CPU) -> Self {
+        let flags = cpu.mcusr().read();
+        let cause = if flags.porf().bit_is_set() {
+            Self::PowerOn
+        } else if flags.wdrf().bit_is_set() {
+            Self::Watchdog
+        } else if flags.borf().bit_is_set() {
+            Self::Brownout
+        } else if flags.extrf().bit_is_set() {
+            Self::External
+        } else if flags.usbrf().bit_is_set() {
+            Self::Usb
+        } else {
+            Self::Unknown
+        };
+        cpu.mcusr().reset();
+        cause
+    }
+
+    /// Decode a cause stored with `as u8`, taking anything unrecognized (e.g. an erased byte) as
+    /// [`Unknown`](Self::Unknown)
+    #[must_use]
+    pub const fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => Self::PowerOn,
+            1 => Self::Brownout,
+            2 => Self::Watchdog,
+            3 => Self::External,
+            4 => Self::Usb,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Name of the cause, padded to 9 characters for display
+    #[must_use]
+    pub const fn name(self) -> &'static [u8; 9] {
+        match self {
+            Self::PowerOn => b"Power-on ",
+            Self::Brownout => b"Brown-out",
+            Self::Watchdog => b"Watchdog ",
+            Self::External => b"External ",
+            Self::Usb => b"USB      ",
+            Self::Unknown => b"Unknown  ",
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! ds1307 RTC abstractions and API
 
-use core::marker::PhantomData;
+use core::{borrow::BorrowMut, marker::PhantomData};
 
 use arduino_hal::{i2c::Direction, I2c};
 use embedded_hal::i2c::{I2c as I2cTrait, Operation};
@@ -12,17 +12,22 @@ const DS1307_ADDR: u8 = 0x68;
 
 /// ds1307 real-time clock module; interfaced via I2C
 ///
-/// No internal state; can be freely constructed/destructed if the I2c bus must be shared
+/// The bus may be owned outright or borrowed as `&mut I2c`. There is no other internal state, so a
+/// borrowing instance can be constructed for a handful of transactions and dropped again, leaving
+/// the bus free for other devices in between
 #[must_use]
-pub struct DS1307<RAM = [u8; 56]> {
-    i2c: I2c,
+pub struct DS1307<RAM = [u8; 56], BUS = I2c> {
+    i2c: BUS,
     _ram: PhantomData<RAM>,
 }
 
 // Misc clock functions
-impl<RAM> DS1307<RAM> {
-    /// Connect to ds1307 by taking ownership of the I2C bus
-    pub const fn new(i2c: I2c) -> Self {
+impl<RAM, BUS> DS1307<RAM, BUS>
+where
+    BUS: BorrowMut<I2c>,
+{
+    /// Connect to ds1307 by taking ownership of, or borrowing, the I2C bus
+    pub const fn new(i2c: BUS) -> Self {
         Self {
             i2c,
             _ram: PhantomData,
@@ -31,53 +36,80 @@ impl<RAM> DS1307<RAM> {
 
     /// Disconnect to release the I2C bus
     #[must_use]
-    pub const fn release(self) -> I2c {
+    pub fn release(self) -> BUS {
         self.i2c
     }
 
+    fn bus(&mut self) -> &mut I2c {
+        self.i2c.borrow_mut()
+    }
+
     /// Returns `true` if the clock responds to a ping
     pub fn is_connected(&mut self) -> bool {
-        self.i2c.ping_device(DS1307_ADDR, Direction::Read).is_ok()
+        self.bus().ping_device(DS1307_ADDR, Direction::Read).is_ok()
     }
 
-    /// Checks time data on-chip, corrects any invalid values, and returns whether corrections were
-    /// made
+    /// Checks time data on-chip, corrects any invalid values, and reports which fields were
+    /// corrected
+    ///
+    /// Invalid fields are reset to their defaults, except the day of the week which is recalculated
+    /// from the (corrected) date, and a date past the end of its month (e.g. February 29 in a
+    /// common year, or April 31) which is clamped to the month's last day; nothing is written if
+    /// every field was valid
+    ///
+    /// The corrected time is written back as a whole, like [`Self::set_time`], in the hour mode
+    /// that was read. Should the clock tick between the read and the write, the write replaces the
+    /// tick with the consistent snapshot taken before it, so the clock ends up at most a second
+    /// behind rather than with a rollover applied to some fields and not others
     ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
-    pub fn validate(&mut self) -> I2cResult<bool> {
+    pub fn validate(&mut self) -> I2cResult<ValidationReport> {
         let mut buf = [0u8; 7];
-        self.i2c.write_read(DS1307_ADDR, &[0], &mut buf)?;
+        self.bus().write_read(DS1307_ADDR, &[0], &mut buf)?;
 
-        let mut valid = true;
+        let mut report = ValidationReport::VALID;
 
         let year = Year::try_from_bcd(buf[6])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::YEAR))
             .unwrap_or_default();
         let month = Month::try_from_bcd(buf[5])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::MONTH))
             .unwrap_or_default();
         let date = Date::try_from_bcd_with_ym(buf[4], year, month)
-            .inspect_err(|_| valid = false)
+            .or_else(|bcd| {
+                Date::try_from_bcd(bcd)
+                    .map(|_| {
+                        report.insert(ValidationReport::CLAMPED);
+                        Date::from_bin(month.length(year.is_leap()))
+                    })
+                    .inspect_err(|_| report.insert(ValidationReport::DATE))
+            })
             .unwrap_or_default();
         let day = Day::try_from_bcd(buf[3])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::DAY))
             .map(|day| {
                 let true_day = Day::from_ymd(year, month, date);
-                valid &= day == true_day;
+                if day != true_day {
+                    report.insert(ValidationReport::DAY);
+                }
                 true_day
             })
             .unwrap_or_default();
         let hours = Hours::try_from_bcd(buf[2])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::HOURS))
             .unwrap_or_default();
         let minutes = Minutes::try_from_bcd(buf[1])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::MINUTES))
             .unwrap_or_default();
         let seconds = Seconds::try_from_bcd(buf[0])
-            .inspect_err(|_| valid = false)
+            .inspect_err(|_| report.insert(ValidationReport::SECONDS))
             .unwrap_or_default();
 
+        if report.is_valid() {
+            return Ok(report);
+        }
+
         let valid_time = RTCTime {
             seconds,
             minutes,
@@ -88,7 +120,8 @@ impl<RAM> DS1307<RAM> {
             year,
         };
 
-        self.set_time(valid_time).map(|()| valid)
+        self.write_time(valid_time, HourMode::from_bcd(buf[2]))
+            .map(|()| report)
     }
 
     /// Reset the clock to [`RTCTime::EPOCH`]
@@ -106,7 +139,18 @@ impl<RAM> DS1307<RAM> {
     pub fn halt_clock(&mut self) -> I2cResult {
         let seconds = self.get_seconds()?;
         let buf = [0, seconds.bcd() | 0b1000_0000];
-        self.i2c.write(DS1307_ADDR, &buf)
+        self.bus().write(DS1307_ADDR, &buf)
+    }
+
+    /// Returns `true` if the clock halt bit is set, meaning the clock is not keeping time
+    ///
+    /// The bit is set when the [`DS1307`] first powers up without a backup battery, so a halted
+    /// clock should be assumed to hold an invalid time until it is set again
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    pub fn is_halted(&mut self) -> I2cResult<bool> {
+        self.get_seconds().map(Seconds::is_halted)
     }
 
     /// Clear the clock halt bit to enable timekeeping
@@ -116,7 +160,7 @@ impl<RAM> DS1307<RAM> {
     pub fn start_clock(&mut self) -> I2cResult {
         let seconds = self.get_seconds()?;
         let buf = [0, seconds.bcd() & 0b0111_1111];
-        self.i2c.write(DS1307_ADDR, &buf)
+        self.bus().write(DS1307_ADDR, &buf)
     }
 
     /// Enable square wave output
@@ -125,8 +169,9 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn sqw_enable(&mut self) -> I2cResult {
         let mut control = [0u8];
-        self.i2c.write_read(DS1307_ADDR, &[7], &mut control)?;
-        self.i2c.write(DS1307_ADDR, &[7, control[0] | 0b0001_0000])
+        self.bus().write_read(DS1307_ADDR, &[7], &mut control)?;
+        self.bus()
+            .write(DS1307_ADDR, &[7, control[0] | 0b0001_0000])
     }
 
     /// Disable square wave output
@@ -135,8 +180,9 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn sqw_disable(&mut self) -> I2cResult {
         let mut control = [0u8];
-        self.i2c.write_read(DS1307_ADDR, &[7], &mut control)?;
-        self.i2c.write(DS1307_ADDR, &[7, control[0] & 0b1110_1111])
+        self.bus().write_read(DS1307_ADDR, &[7], &mut control)?;
+        self.bus()
+            .write(DS1307_ADDR, &[7, control[0] & 0b1110_1111])
     }
 
     /// Get square wave output frequency
@@ -145,7 +191,7 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn sqw_get_freq(&mut self) -> I2cResult<Freq> {
         let mut control = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[7], &mut control)
             .map(|()| Freq::from_bits(control[0] & 0x3))
     }
@@ -156,32 +202,59 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn sqw_set_freq(&mut self, freq: Freq) -> I2cResult {
         let mut control = [0u8];
-        self.i2c.write_read(DS1307_ADDR, &[7], &mut control)?;
-        self.i2c
+        self.bus().write_read(DS1307_ADDR, &[7], &mut control)?;
+        self.bus()
             .write(DS1307_ADDR, &[7, (control[0] & 0xfc) | freq as u8])
     }
+
+    /// Enable square wave output at 1 Hz, giving a once-per-second tick in step with the seconds
+    /// register
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    pub fn sqw_enable_1hz(&mut self) -> I2cResult {
+        let mut control = [0u8];
+        self.bus().write_read(DS1307_ADDR, &[7], &mut control)?;
+        self.bus().write(
+            DS1307_ADDR,
+            &[7, (control[0] & 0xfc) | 0b0001_0000 | Freq::Hz1 as u8],
+        )
+    }
 }
 
 // Time getters
-impl<RAM> DS1307<RAM> {
+impl<RAM, BUS> DS1307<RAM, BUS>
+where
+    BUS: BorrowMut<I2c>,
+{
     /// Get complete date and time reading
     ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_time(&mut self) -> I2cResult<RTCTime> {
         let mut buf = [0u8; 7];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[0], &mut buf)
             .map(|()| RTCTime::from_bcd(buf))
     }
 
+    /// Get complete date and time reading, or `None` if the clock is halted and the reading
+    /// therefore cannot be trusted
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    pub fn get_running_time(&mut self) -> I2cResult<Option<RTCTime>> {
+        self.get_time()
+            .map(|time| (!time.seconds.is_halted()).then_some(time))
+    }
+
     /// Get seconds
     ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_seconds(&mut self) -> I2cResult<Seconds> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[0], &mut buf)
             .map(|()| Seconds::from_bcd(buf[0]))
     }
@@ -192,7 +265,7 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_minutes(&mut self) -> I2cResult<Minutes> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[1], &mut buf)
             .map(|()| Minutes::from_bcd(buf[0]))
     }
@@ -203,18 +276,29 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_hours(&mut self) -> I2cResult<Hours> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[2], &mut buf)
             .map(|()| Hours::from_bcd(buf[0]))
     }
 
+    /// Get the format the hours register is currently kept in
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    pub fn get_hour_mode(&mut self) -> I2cResult<HourMode> {
+        let mut buf = [0u8];
+        self.bus()
+            .write_read(DS1307_ADDR, &[2], &mut buf)
+            .map(|()| HourMode::from_bcd(buf[0]))
+    }
+
     /// Get day
     ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_day(&mut self) -> I2cResult<Day> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[3], &mut buf)
             .map(|()| Day::from_bcd(buf[0]))
     }
@@ -225,7 +309,7 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_date(&mut self) -> I2cResult<Date> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[4], &mut buf)
             .map(|()| Date::from_bcd(buf[0]))
     }
@@ -236,7 +320,7 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_month(&mut self) -> I2cResult<Month> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[5], &mut buf)
             .map(|()| Month::from_bcd(buf[0]))
     }
@@ -247,22 +331,38 @@ impl<RAM> DS1307<RAM> {
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_year(&mut self) -> I2cResult<Year> {
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[6], &mut buf)
             .map(|()| Year::from_bcd(buf[0]))
     }
 }
 
 // Time setters
-impl<RAM> DS1307<RAM> {
+impl<RAM, BUS> DS1307<RAM, BUS>
+where
+    BUS: BorrowMut<I2c>,
+{
     /// Set complete date and time reading
     ///
+    /// The hours are written in whichever [`HourMode`] the chip is already in
+    ///
+    /// All seven registers are written in a single burst starting with the seconds. Writing the
+    /// seconds register resets the DS1307's countdown chain, so the next tick is a full second
+    /// away and can't roll over partway through the burst; no halting is needed. To change several
+    /// fields at once, prefer this over the individual setters, between which a tick could carry
+    /// into a field that is about to be overwritten
+    ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_time(&mut self, time: RTCTime) -> I2cResult {
-        self.i2c.transaction(
+        let mode = self.get_hour_mode()?;
+        self.write_time(time, mode)
+    }
+
+    fn write_time(&mut self, time: RTCTime, mode: HourMode) -> I2cResult {
+        self.bus().transaction(
             DS1307_ADDR,
-            &mut [Operation::Write(&[0]), Operation::Write(&time.bcd())],
+            &mut [Operation::Write(&[0]), Operation::Write(&time.bcd_in(mode))],
         )
     }
 
@@ -271,7 +371,7 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_seconds(&mut self, seconds: Seconds) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[0, seconds.bcd()])
+        self.bus().write(DS1307_ADDR, &[0, seconds.bcd()])
     }
 
     /// Set minutes
@@ -279,15 +379,32 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_minutes(&mut self, minutes: Minutes) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[1, minutes.bcd()])
+        self.bus().write(DS1307_ADDR, &[1, minutes.bcd()])
     }
 
     /// Set hours
     ///
+    /// The hours are written in whichever [`HourMode`] the chip is already in
+    ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_hours(&mut self, hours: Hours) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[2, hours.bcd_24h()])
+        let mode = self.get_hour_mode()?;
+        self.bus().write(DS1307_ADDR, &[2, hours.bcd_in(mode)])
+    }
+
+    /// Switch the hours register to the given format, keeping the current hour
+    ///
+    /// Subsequent writes through [`Self::set_hours`] and [`Self::set_time`] keep this format. An
+    /// invalid hours value is reset to 0
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    pub fn set_hour_mode(&mut self, mode: HourMode) -> I2cResult {
+        let mut buf = [0u8];
+        self.bus().write_read(DS1307_ADDR, &[2], &mut buf)?;
+        let hours = Hours::try_from_bcd(buf[0]).unwrap_or_default();
+        self.bus().write(DS1307_ADDR, &[2, hours.bcd_in(mode)])
     }
 
     /// Set day
@@ -295,7 +412,7 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_day(&mut self, day: Day) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[3, day.bcd()])
+        self.bus().write(DS1307_ADDR, &[3, day.bcd()])
     }
 
     /// Set date
@@ -303,7 +420,7 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_date(&mut self, date: Date) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[4, date.bcd()])
+        self.bus().write(DS1307_ADDR, &[4, date.bcd()])
     }
 
     /// Set month
@@ -311,7 +428,7 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_month(&mut self, month: Month) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[5, month.bcd()])
+        self.bus().write(DS1307_ADDR, &[5, month.bcd()])
     }
 
     /// Set year
@@ -319,19 +436,22 @@ impl<RAM> DS1307<RAM> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_year(&mut self, year: Year) -> I2cResult {
-        self.i2c.write(DS1307_ADDR, &[6, year.bcd()])
+        self.bus().write(DS1307_ADDR, &[6, year.bcd()])
     }
 }
 
 // RAM-related methods
-impl DS1307<[u8; 56]> {
+impl<BUS> DS1307<[u8; 56], BUS>
+where
+    BUS: BorrowMut<I2c>,
+{
     /// Get entire RAM block
     ///
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn get_ram(&mut self) -> I2cResult<[u8; 56]> {
         let mut buf = [0u8; 56];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[8], &mut buf)
             .map(|()| buf)
     }
@@ -341,12 +461,39 @@ impl DS1307<[u8; 56]> {
     /// # Errors
     /// Returns an error if the something goes wrong on the I2C bus
     pub fn set_ram(&mut self, ram: [u8; 56]) -> I2cResult {
-        self.i2c.transaction(
+        self.bus().transaction(
             DS1307_ADDR,
             &mut [Operation::Write(&[8]), Operation::Write(&ram)],
         )
     }
 
+    /// Read a range of RAM starting at `offset` into `buf`
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    ///
+    /// # Panics
+    /// Panics if the range extends past the end of RAM
+    pub fn read_ram(&mut self, offset: u8, buf: &mut [u8]) -> I2cResult {
+        assert!(offset as usize + buf.len() <= 56, "Invalid RAM range!");
+        self.bus().write_read(DS1307_ADDR, &[8 + offset], buf)
+    }
+
+    /// Write `buf` to a range of RAM starting at `offset`
+    ///
+    /// # Errors
+    /// Returns an error if the something goes wrong on the I2C bus
+    ///
+    /// # Panics
+    /// Panics if the range extends past the end of RAM
+    pub fn write_ram(&mut self, offset: u8, buf: &[u8]) -> I2cResult {
+        assert!(offset as usize + buf.len() <= 56, "Invalid RAM range!");
+        self.bus().transaction(
+            DS1307_ADDR,
+            &mut [Operation::Write(&[8 + offset]), Operation::Write(buf)],
+        )
+    }
+
     /// Read a specified byte from RAM
     ///
     /// # Errors
@@ -357,7 +504,7 @@ impl DS1307<[u8; 56]> {
     pub fn get_byte(&mut self, i: u8) -> I2cResult<u8> {
         assert!((0..56).contains(&i), "Invalid byte index!");
         let mut buf = [0u8];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[8 + i], &mut buf)
             .map(|()| buf[0])
     }
@@ -371,7 +518,7 @@ impl DS1307<[u8; 56]> {
     /// Panics if the index is outside the range 0..56
     pub fn set_byte(&mut self, i: u8, byte: u8) -> I2cResult {
         assert!((0..56).contains(&i), "Invalid byte index!");
-        self.i2c.write(DS1307_ADDR, &[8 + i, byte])
+        self.bus().write(DS1307_ADDR, &[8 + i, byte])
     }
 
     /// Read a specified aligned word from RAM in little-endian
@@ -384,7 +531,7 @@ impl DS1307<[u8; 56]> {
     pub fn get_word(&mut self, i: u8) -> I2cResult<u16> {
         assert!((0..28).contains(&i), "Invalid word index!");
         let mut buf = [0u8; 2];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[8 + i], &mut buf)
             .map(|()| u16::from_le_bytes(buf))
     }
@@ -399,7 +546,7 @@ impl DS1307<[u8; 56]> {
     pub fn set_word(&mut self, i: u8, word: u16) -> I2cResult {
         assert!((0..28).contains(&i), "Invalid word index!");
         let [b0, b1] = word.to_le_bytes();
-        self.i2c.write(DS1307_ADDR, &[8 + i, b0, b1])
+        self.bus().write(DS1307_ADDR, &[8 + i, b0, b1])
     }
 
     /// Read a specified aligned dword from RAM in little-endian
@@ -412,7 +559,7 @@ impl DS1307<[u8; 56]> {
     pub fn get_dword(&mut self, i: u8) -> I2cResult<u32> {
         assert!((0..14).contains(&i), "Invalid dword index!");
         let mut buf = [0u8; 4];
-        self.i2c
+        self.bus()
             .write_read(DS1307_ADDR, &[8 + i], &mut buf)
             .map(|()| u32::from_le_bytes(buf))
     }
@@ -427,7 +574,72 @@ impl DS1307<[u8; 56]> {
     pub fn set_dword(&mut self, i: u8, dword: u32) -> I2cResult {
         assert!((0..14).contains(&i), "Invalid dword index!");
         let [b0, b1, b2, b3] = dword.to_le_bytes();
-        self.i2c.write(DS1307_ADDR, &[8 + i, b0, b1, b2, b3])
+        self.bus().write(DS1307_ADDR, &[8 + i, b0, b1, b2, b3])
+    }
+}
+
+/// Set of time fields found invalid and corrected by [`DS1307::validate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[must_use]
+pub struct ValidationReport(u8);
+
+impl ValidationReport {
+    /// No fields were invalid
+    pub const VALID: Self = Self(0);
+
+    /// Seconds were invalid and reset to 0
+    pub const SECONDS: Self = Self(1 << 0);
+
+    /// Minutes were invalid and reset to 0
+    pub const MINUTES: Self = Self(1 << 1);
+
+    /// Hours were invalid and reset to 0
+    pub const HOURS: Self = Self(1 << 2);
+
+    /// Day of the week was invalid or did not match the date, and was recalculated
+    pub const DAY: Self = Self(1 << 3);
+
+    /// Date was invalid and reset to the 1st
+    pub const DATE: Self = Self(1 << 4);
+
+    /// Month was invalid and reset to January
+    pub const MONTH: Self = Self(1 << 5);
+
+    /// Year was invalid and reset to 2000
+    pub const YEAR: Self = Self(1 << 6);
+
+    /// Date was past the end of the month, and was clamped to its last day
+    pub const CLAMPED: Self = Self(1 << 7);
+
+    /// Returns `true` if no fields were invalid
+    #[must_use]
+    pub const fn is_valid(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if every correction was a repair that leaves the time trustworthy, i.e. only
+    /// [`Self::DAY`] and [`Self::CLAMPED`]
+    #[must_use]
+    pub const fn is_trustworthy(self) -> bool {
+        self.0 & !(Self::DAY.0 | Self::CLAMPED.0) == 0
+    }
+
+    /// Returns `true` if all fields in `other` were invalid
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Add the fields in `other` to the report
+    pub const fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Returns the raw bitflags; bit 0 is seconds through bit 6 is year, in register order, and bit
+    /// 7 is a clamped date
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
     }
 }
 
@@ -456,6 +668,29 @@ impl Freq {
     }
 }
 
+/// Format of the [`DS1307`]'s hours register
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HourMode {
+    /// 0-23
+    #[default]
+    H24,
+
+    /// 1-12 with an AM/PM flag
+    H12,
+}
+
+impl HourMode {
+    /// Detect the format of a raw hours register value
+    #[must_use]
+    pub const fn from_bcd(bcd: u8) -> Self {
+        if bcd & 0x40 == 0 {
+            Self::H24
+        } else {
+            Self::H12
+        }
+    }
+}
+
 /// Complete time reading; layout identical to [DS1307] internally
 #[expect(missing_docs, reason = "self-explanatory variants")]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -485,6 +720,9 @@ impl RTCTime {
         year: Year(0),
     };
 
+    /// The number of seconds in the [`DS1307`]'s 100 year range
+    const CENTURY_SECS: u32 = 36_525 * 86_400;
+
     /// Convenience constructor for midnight (00:00:00) on a given date
     pub const fn new_date(year: u8, month: Month, date: u8) -> Self {
         let year = Year::from_bin(year);
@@ -500,6 +738,37 @@ impl RTCTime {
         }
     }
 
+    /// Construct from binary (plain integer) values, calculating the day of the week from the date;
+    /// `year` counts from 2000 and `hours` is in 24-hour format
+    ///
+    /// # Panics
+    /// Panics if any value is out of range, including a date past the end of the given month
+    pub const fn from_bin(
+        year: u8,
+        month: u8,
+        date: u8,
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+    ) -> Self {
+        let year = Year::from_bin(year);
+        let month = Month::from_bin(month);
+        assert!(
+            date <= month.length(year.is_leap()),
+            "date out of range for month"
+        );
+        let date = Date::from_bin(date);
+        Self {
+            seconds: Seconds::from_bin(seconds),
+            minutes: Minutes::from_bin(minutes),
+            hours: Hours::from_bin(hours),
+            day: Day::from_ymd(year, month, date),
+            date,
+            month,
+            year,
+        }
+    }
+
     /// Construct from BCD representation
     ///
     /// # Errors
@@ -538,13 +807,19 @@ impl RTCTime {
         panic!();
     }
 
-    /// Returns value as BCD
+    /// Returns value as BCD, with hours in 24-hour format
     #[must_use]
     pub const fn bcd(self) -> [u8; 7] {
+        self.bcd_in(HourMode::H24)
+    }
+
+    /// Returns value as BCD, with hours in the given format
+    #[must_use]
+    pub const fn bcd_in(self, mode: HourMode) -> [u8; 7] {
         [
             self.seconds.bcd(),
             self.minutes.bcd(),
-            self.hours.bcd_24h(),
+            self.hours.bcd_in(mode),
             self.day.bcd(),
             self.date.bcd(),
             self.month.bcd(),
@@ -569,6 +844,190 @@ impl RTCTime {
 
         seconds + days as u32 * 86_400
     }
+
+    /// Construct from the number of seconds since the [`DS1307`]'s zero date: Jan 1, 2000
+    ///
+    /// Inverse of [`RTCTime::to_epoch_secs`]; values beyond the 100 years representable by the
+    /// [`DS1307`] wrap around
+    pub const fn from_epoch_secs(secs: u32) -> Self {
+        let secs = secs % Self::CENTURY_SECS;
+
+        let mut days = secs / 86_400;
+        let secs_of_day = secs % 86_400;
+
+        let mut year = 0;
+        loop {
+            let year_len = 365 + Year::from_bin(year).is_leap() as u32;
+            if days < year_len {
+                break;
+            }
+            days -= year_len;
+            year += 1;
+        }
+        let year = Year::from_bin(year);
+
+        let mut month = Month::January;
+        loop {
+            let month_len = month.length(year.is_leap()) as u32;
+            if days < month_len {
+                break;
+            }
+            days -= month_len;
+            month = month.next();
+        }
+        let date = Date::from_bin(days as u8 + 1);
+
+        Self {
+            seconds: Seconds::from_bin((secs_of_day % 60) as u8),
+            minutes: Minutes::from_bin((secs_of_day / 60 % 60) as u8),
+            hours: Hours::from_bin((secs_of_day / 3600) as u8),
+            day: Day::from_ymd(year, month, date),
+            date,
+            month,
+            year,
+        }
+    }
+
+    /// Returns the time `secs` seconds later, carrying into the minutes, hours, date, month and
+    /// year as needed
+    ///
+    /// Like the [`DS1307`] itself, the result wraps around from 2099 to 2000
+    pub const fn add_seconds(self, secs: u32) -> Self {
+        let start = self.to_epoch_secs();
+        let sum = start.wrapping_add(secs % Self::CENTURY_SECS);
+        Self::from_epoch_secs(if sum < start || sum >= Self::CENTURY_SECS {
+            sum.wrapping_sub(Self::CENTURY_SECS)
+        } else {
+            sum
+        })
+    }
+
+    /// Returns the time `minutes` minutes later; see [`RTCTime::add_seconds`]
+    pub const fn add_minutes(self, minutes: u32) -> Self {
+        self.add_seconds(minutes % (Self::CENTURY_SECS / 60) * 60)
+    }
+
+    /// Parse from ASCII in the form `YYYY-MM-DD HH:MM:SS` (24-hour); the seconds may be omitted,
+    /// and a `T` is also accepted between the date and the time
+    ///
+    /// The day of the week is calculated from the date
+    ///
+    /// # Errors
+    /// Returns an error if the string is malformed, the year is outside the [`DS1307`]'s 2000-2099
+    /// range, or any field is out of range
+    pub const fn parse(s: &str) -> Result<Self, ParseError> {
+        let bytes = s.as_bytes();
+
+        if !(bytes.len() == 16 || bytes.len() == 19)
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || !(bytes[10] == b' ' || bytes[10] == b'T')
+            || bytes[13] != b':'
+            || (bytes.len() == 19 && bytes[16] != b':')
+        {
+            return Err(ParseError::Format);
+        }
+
+        let year = match parse_bcd_pair(bytes, 0) {
+            Ok(0x20) => match parse_bcd_pair(bytes, 2) {
+                Ok(bcd) => match Year::try_from_bcd(bcd) {
+                    Ok(year) => year,
+                    Err(_) => return Err(ParseError::Year),
+                },
+                Err(e) => return Err(e),
+            },
+            Ok(_) => return Err(ParseError::Year),
+            Err(e) => return Err(e),
+        };
+        let month = match parse_bcd_pair(bytes, 5) {
+            Ok(bcd) => match Month::try_from_bcd(bcd) {
+                Ok(month) => month,
+                Err(_) => return Err(ParseError::Month),
+            },
+            Err(e) => return Err(e),
+        };
+        let date = match parse_bcd_pair(bytes, 8) {
+            Ok(bcd) => match Date::try_from_bcd_with_ym(bcd, year, month) {
+                Ok(date) => date,
+                Err(_) => return Err(ParseError::Date),
+            },
+            Err(e) => return Err(e),
+        };
+        let hours = match parse_bcd_pair(bytes, 11) {
+            // Reject values that would otherwise be interpreted as 12-hour format
+            Ok(bcd) if bcd > 0x23 => return Err(ParseError::Hours),
+            Ok(bcd) => match Hours::try_from_bcd(bcd) {
+                Ok(hours) => hours,
+                Err(_) => return Err(ParseError::Hours),
+            },
+            Err(e) => return Err(e),
+        };
+        let minutes = match parse_bcd_pair(bytes, 14) {
+            Ok(bcd) => match Minutes::try_from_bcd(bcd) {
+                Ok(minutes) => minutes,
+                Err(_) => return Err(ParseError::Minutes),
+            },
+            Err(e) => return Err(e),
+        };
+        let seconds = if bytes.len() == 19 {
+            match parse_bcd_pair(bytes, 17) {
+                // Reject values that only pass the check because of the clock halt bit
+                Ok(bcd) if bcd > 0x59 => return Err(ParseError::Seconds),
+                Ok(bcd) => match Seconds::try_from_bcd(bcd) {
+                    Ok(seconds) => seconds,
+                    Err(_) => return Err(ParseError::Seconds),
+                },
+                Err(e) => return Err(e),
+            }
+        } else {
+            Seconds(0)
+        };
+
+        Ok(Self {
+            seconds,
+            minutes,
+            hours,
+            day: Day::from_ymd(year, month, date),
+            date,
+            month,
+            year,
+        })
+    }
+}
+
+/// Reason an [`RTCTime`] could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// Wrong length, misplaced separator, or non-digit character
+    Format,
+
+    /// Year outside 2000-2099
+    Year,
+
+    /// Month outside 1-12
+    Month,
+
+    /// Date outside the length of the month
+    Date,
+
+    /// Hours outside 0-23
+    Hours,
+
+    /// Minutes outside 0-59
+    Minutes,
+
+    /// Seconds outside 0-59
+    Seconds,
+}
+
+/// Read two ASCII digits starting at `i` as a BCD byte
+const fn parse_bcd_pair(bytes: &[u8], i: usize) -> Result<u8, ParseError> {
+    let (tens, ones) = (bytes[i], bytes[i + 1]);
+    if tens.is_ascii_digit() && ones.is_ascii_digit() {
+        Ok((tens - b'0') << 4 | (ones - b'0'))
+    } else {
+        Err(ParseError::Format)
+    }
 }
 
 /// Seconds encoded as 2 digit BCD
@@ -626,6 +1085,12 @@ impl Seconds {
     pub const fn bcd(self) -> u8 {
         self.0 & 0x7f
     }
+
+    /// Returns `true` if the clock halt bit (bit 7) was set when this value was read
+    #[must_use]
+    pub const fn is_halted(self) -> bool {
+        self.0 & 0x80 != 0
+    }
 }
 
 /// Minutes encoded as 2 digit BCD
@@ -777,6 +1242,15 @@ impl Hours {
         let bcd_h12 = if h12 >= 10 { h12 - 10 + 0x10 } else { h12 };
         0x40 | (if pm { 0x20 } else { 0 }) | bcd_h12
     }
+
+    /// Returns value as BCD in the given format
+    #[must_use]
+    pub const fn bcd_in(self, mode: HourMode) -> u8 {
+        match mode {
+            HourMode::H24 => self.bcd_24h(),
+            HourMode::H12 => self.bcd_12h(),
+        }
+    }
 }
 
 /// Day of the week
@@ -1035,6 +1509,28 @@ impl Date {
     }
 }
 
+const _: () = assert!(
+    Date::try_from_bcd_with_ym(0x30, Year::from_bin(24), Month::February).is_err(),
+    "February 30th must be rejected, even in a leap year"
+);
+const _: () = assert!(
+    Date::try_from_bcd_with_ym(0x29, Year::from_bin(23), Month::February).is_err()
+        && Date::try_from_bcd_with_ym(0x29, Year::from_bin(24), Month::February).is_ok(),
+    "February 29th must only be accepted in a leap year"
+);
+const _: () = assert!(
+    Date::try_from_bcd_with_ym(0x31, Year::from_bin(24), Month::April).is_err()
+        && Date::try_from_bcd_with_ym(0x30, Year::from_bin(24), Month::April).is_ok(),
+    "April 31st must be rejected"
+);
+const _: () = assert!(
+    Month::February.length(false) == 28
+        && Month::February.length(true) == 29
+        && Month::April.length(true) == 30
+        && Month::December.length(false) == 31,
+    "months must have their calendar lengths"
+);
+
 impl Default for Date {
     fn default() -> Self {
         Self(1)
@@ -1096,7 +1592,7 @@ impl Month {
     #[must_use]
     pub const fn from_bin(value: u8) -> Self {
         assert!(value != 0 && value <= 12, "value out of range");
-        Self::from_bcd(value + if value > 9 { 6 } else { 1 })
+        Self::from_bcd(value + if value > 9 { 6 } else { 0 })
     }
 
     /// Name of [Month] as text
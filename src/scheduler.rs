@@ -0,0 +1,216 @@
+//! Time-of-day event scheduling and weekly setpoint programs
+//!
+//! The [`DS1307`] has no alarm registers, so scheduled times are compared against the clock in
+//! software; polling once a second (e.g. on each tick of the 1 Hz square wave) is sufficient
+//!
+//! [`DS1307`]: crate::rtc::DS1307
+
+use crate::rtc::{Day, Hours, Minutes, RTCTime};
+
+#[derive(Clone, Copy)]
+struct Event<A> {
+    hours: Hours,
+    minutes: Minutes,
+    action: A,
+    fired: bool,
+}
+
+/// Fixed-capacity list of daily events, each firing once when the clock reaches its time of day
+#[must_use]
+pub struct Scheduler<A, const N: usize> {
+    events: [Option<Event<A>>; N],
+}
+
+impl<A, const N: usize> Scheduler<A, N>
+where
+    A: Copy,
+{
+    /// Create an empty scheduler
+    pub const fn new() -> Self {
+        Self { events: [None; N] }
+    }
+
+    /// Schedule `action` to fire every day at `hours`:`minutes`, returning `false` if the schedule
+    /// is full
+    pub const fn add(&mut self, hours: Hours, minutes: Minutes, action: A) -> bool {
+        let mut i = 0;
+        while i < N {
+            if self.events[i].is_none() {
+                self.events[i] = Some(Event {
+                    hours,
+                    minutes,
+                    action,
+                    fired: false,
+                });
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Remove all events
+    pub const fn clear(&mut self) {
+        self.events = [None; N];
+    }
+
+    /// Compare the scheduled events against the given time, returning the next action that is due
+    ///
+    /// Each event fires only once during its scheduled minute, so this should be called repeatedly
+    /// until it returns `None` to collect every action that is due
+    pub const fn poll(&mut self, time: RTCTime) -> Option<A> {
+        let mut i = 0;
+        while i < N {
+            if let Some(ref mut event) = self.events[i] {
+                let due = event.hours.bcd_24h() == time.hours.bcd_24h()
+                    && event.minutes.bcd() == time.minutes.bcd();
+                if !due {
+                    event.fired = false;
+                } else if !event.fired {
+                    event.fired = true;
+                    return Some(event.action);
+                }
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+impl<A, const N: usize> Default for Scheduler<A, N>
+where
+    A: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Setpoint {
+    minute_of_day: u16,
+    temp: f32,
+}
+
+/// Fixed-capacity setpoint program for each day of the week
+///
+/// Each day holds up to `N` entries, each taking effect from its time of day until the next entry,
+/// which may fall on a later day; a day without entries carries on with the last setpoint of the
+/// days before it
+#[must_use]
+pub struct WeeklySchedule<const N: usize> {
+    days: [[Option<Setpoint>; N]; 7],
+}
+
+impl<const N: usize> WeeklySchedule<N> {
+    /// Create an empty program
+    pub const fn new() -> Self {
+        Self {
+            days: [[None; N]; 7],
+        }
+    }
+
+    /// Set the setpoint to `temp` from `hours`:`minutes` on `day`, replacing any entry already at
+    /// that time; returns `false` if the day is full
+    pub const fn add(&mut self, day: Day, hours: Hours, minutes: Minutes, temp: f32) -> bool {
+        let minute_of_day = minute_of_day(hours, minutes);
+        let entries = &mut self.days[day as usize - 1];
+
+        let mut free = None;
+        let mut i = 0;
+        while i < N {
+            match entries[i] {
+                Some(ref mut entry) if entry.minute_of_day == minute_of_day => {
+                    entry.temp = temp;
+                    return true;
+                }
+                None if free.is_none() => free = Some(i),
+                _ => {}
+            }
+            i += 1;
+        }
+
+        if let Some(i) = free {
+            entries[i] = Some(Setpoint {
+                minute_of_day,
+                temp,
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove every entry on `day`
+    pub const fn clear_day(&mut self, day: Day) {
+        self.days[day as usize - 1] = [None; N];
+    }
+
+    /// Remove every entry
+    pub const fn clear(&mut self) {
+        self.days = [[None; N]; 7];
+    }
+
+    /// Checks if the program has no entries at all
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        let mut d = 0;
+        while d < 7 {
+            let mut i = 0;
+            while i < N {
+                if self.days[d][i].is_some() {
+                    return false;
+                }
+                i += 1;
+            }
+            d += 1;
+        }
+        true
+    }
+
+    /// Setpoint in effect at the given time, or `None` if the program is empty
+    #[must_use]
+    pub const fn setpoint(&self, time: RTCTime) -> Option<f32> {
+        let now = minute_of_day(time.hours, time.minutes);
+        let mut d = time.day as usize - 1;
+
+        // Look back through today before now, then each previous day in full, and finally the
+        // rest of today a week ago
+        let mut days_back = 0;
+        while days_back <= 7 {
+            let mut latest: Option<Setpoint> = None;
+            let mut i = 0;
+            while i < N {
+                if let Some(entry) = self.days[d][i] {
+                    let in_range = days_back > 0 || entry.minute_of_day <= now;
+                    let later = match latest {
+                        Some(l) => entry.minute_of_day > l.minute_of_day,
+                        None => true,
+                    };
+                    if in_range && later {
+                        latest = Some(entry);
+                    }
+                }
+                i += 1;
+            }
+
+            if let Some(entry) = latest {
+                return Some(entry.temp);
+            }
+
+            d = (d + 6) % 7;
+            days_back += 1;
+        }
+        None
+    }
+}
+
+impl<const N: usize> Default for WeeklySchedule<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn minute_of_day(hours: Hours, minutes: Minutes) -> u16 {
+    hours.bin() as u16 * 60 + minutes.bin() as u16
+}
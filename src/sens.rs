@@ -1,36 +1,150 @@
 //! Sensor abstractions for climate controller
 
-use core::{cell::Cell, f32};
+use core::{
+    cell::{Cell, RefCell},
+    f32,
+};
 
 use arduino_hal::{
     adc::AdcSettings,
-    hal::port::{PF0, PF1, PF4, PF5},
-    pac::ADC,
+    clock::Clock,
+    hal::port::{PF0, PF1, PF4, PF5, PF6},
+    pac::{ADC, TC3},
     port::{
         mode::{Analog, Floating, Input},
         Pin,
     },
-    Adc,
+    Adc, DefaultClock,
 };
+use avr_device::interrupt::Mutex;
+
+use crate::utils::{is_finite, ln, recip, u16_to_f32};
+
+/// Minimum time in milliseconds between the temperature readings used to calculate slopes
+const SLOPE_INTERVAL: u32 = 1000;
+
+/// Valid range of raw ADC samples; anything closer to the rails indicates a shorted or open circuit
+const VALID_SAMPLES: core::ops::Range<u16> = 8..1016;
+
+/// Number of progressively less sensitive samples taken after the baseline while settling
+const SETTLE_STEPS: u8 = 10;
+
+/// Filtered samples accumulated for a mean before the accumulation restarts, bounding the rounding
+/// error of the running sum
+const MAX_MEAN_SAMPLES: u16 = 1024;
+
+/// Largest supported median filter window
+pub const MAX_MEDIAN_WINDOW: usize = 7;
+
+/// Sets of samples taken in the background, with the times they were taken, that can await
+/// [`Sensorium::collect`] before the oldest are dropped
+pub const SAMPLE_QUEUE_LEN: usize = 8;
+
+/// Supported background sampling rates in hertz; slower rates overflow Timer3's 16-bit TOP, and at
+/// faster ones the ~0.3 ms conversions would take up more than a third of the CPU time
+pub const BACKGROUND_HZ: core::ops::RangeInclusive<u16> = 4..=1000;
+
+/// Analog inputs, held by the sampling interrupt while sampling in the background
+static INPUTS: Mutex<RefCell<Option<Inputs>>> = Mutex::new(RefCell::new(None));
+static QUEUE: Mutex<RefCell<SampleQueue>> = Mutex::new(RefCell::new(SampleQueue::new()));
+
+#[expect(clippy::allow_attributes, reason = "expect somehow doesn't work")]
+#[allow(missing_docs, reason = "macro expansion breaks doc comments")]
+mod interrupts {
+    use super::{INPUTS, QUEUE};
+
+    #[avr_device::interrupt(atmega32u4)]
+    fn TIMER3_COMPA() {
+        avr_device::interrupt::free(|cs| {
+            if let Some(inputs) = INPUTS.borrow(cs).borrow_mut().as_mut() {
+                let samples = inputs.read_sensors();
+                QUEUE
+                    .borrow(cs)
+                    .borrow_mut()
+                    .push(samples, crate::millis::millis());
+            }
+        });
+    }
+}
 
-use crate::utils::{ln, recip, u16_to_f32};
-
-/// The control system's complete sensory apparatus
-#[must_use]
-pub struct Sensorium {
+/// ADC and the analog inputs it converts
+struct Inputs {
     adc: Adc,
     coolant_pin: Pin<Analog, PF5>,
     habitat_pin: Pin<Analog, PF4>,
     condenser_pin: Pin<Analog, PF1>,
-    _pin3: Pin<Analog, PF0>,
+    pin3: Pin<Analog, PF0>,
+    aux_pin: Pin<Analog, PF6>,
+}
+
+impl Inputs {
+    /// Read the raw ADC samples of the coolant, habitat and condenser sensors
+    fn read_sensors(&mut self) -> [u16; 3] {
+        [
+            self.coolant_pin.analog_read(&mut self.adc),
+            self.habitat_pin.analog_read(&mut self.adc),
+            self.condenser_pin.analog_read(&mut self.adc),
+        ]
+    }
+
+    fn read_aux(&mut self, input: AuxInput) -> u16 {
+        match input {
+            AuxInput::A1 => self.aux_pin.analog_read(&mut self.adc),
+            AuxInput::A5 => self.pin3.analog_read(&mut self.adc),
+        }
+    }
+}
+
+/// Ring buffer of sensor samples taken in the background and the times they were taken,
+/// overwriting the oldest when full
+struct SampleQueue {
+    samples: [([u16; 3], u32); SAMPLE_QUEUE_LEN],
+    head: u8,
+    len: u8,
+}
+
+impl SampleQueue {
+    const fn new() -> Self {
+        Self {
+            samples: [([0; 3], 0); SAMPLE_QUEUE_LEN],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    const fn push(&mut self, samples: [u16; 3], now: u32) {
+        let tail = (self.head as usize + self.len as usize) % SAMPLE_QUEUE_LEN;
+        self.samples[tail] = (samples, now);
+        if self.len as usize == SAMPLE_QUEUE_LEN {
+            self.head = ((self.head as usize + 1) % SAMPLE_QUEUE_LEN) as u8;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    const fn pop(&mut self) -> Option<([u16; 3], u32)> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let samples = self.samples[self.head as usize];
+        self.head = ((self.head as usize + 1) % SAMPLE_QUEUE_LEN) as u8;
+        self.len -= 1;
+        Some(samples)
+    }
+}
+
+/// The control system's complete sensory apparatus
+#[must_use]
+pub struct Sensorium {
+    inputs: Option<Inputs>,
 
     coolant_temp: Thermistor,
     habitat_temp: Thermistor,
     condenser_temp: Thermistor,
     _temp3: Thermistor,
 
-    sens: f32,
-    sens_steps: u8,
+    last_slope: u32,
 }
 
 impl Sensorium {
@@ -41,40 +155,169 @@ impl Sensorium {
         a3: Pin<Input<Floating>, PF4>,
         a4: Pin<Input<Floating>, PF1>,
         a5: Pin<Input<Floating>, PF0>,
+        a1: Pin<Input<Floating>, PF6>,
     ) -> Self {
         let mut adc = Adc::new(adc, AdcSettings::default());
 
         Self {
-            coolant_pin: a2.into_analog_input(&mut adc),
-            habitat_pin: a3.into_analog_input(&mut adc),
-            condenser_pin: a4.into_analog_input(&mut adc),
-            _pin3: a5.into_analog_input(&mut adc),
-
-            coolant_temp: Thermistor::new(10_000.0, 3_380.0, 9_820.0),
+            inputs: Some(Inputs {
+                coolant_pin: a2.into_analog_input(&mut adc),
+                habitat_pin: a3.into_analog_input(&mut adc),
+                condenser_pin: a4.into_analog_input(&mut adc),
+                pin3: a5.into_analog_input(&mut adc),
+                aux_pin: a1.into_analog_input(&mut adc),
+                adc,
+            }),
+
+            coolant_temp: Thermistor::with_median(10_000.0, 3_380.0, 9_820.0, 3),
             habitat_temp: Thermistor::new(20_000.0, 3_950.0, 21_440.0),
             condenser_temp: Thermistor::new(50_000.0, 3_950.0, 46_200.0),
             _temp3: Thermistor::new(10_000.0, 3_380.0, 9_860.0),
 
-            adc,
+            last_slope: 0,
+        }
+    }
 
-            sens: 1.0,
-            sens_steps: 10,
+    /// Hand sampling over to a Timer3 interrupt taking `hz` samples per second, so the sampling
+    /// cadence no longer depends on how long each pass of the main loop takes
+    ///
+    /// The samples queue up until [`Sensorium::collect`] feeds them through the filters; only the
+    /// latest [`SAMPLE_QUEUE_LEN`] sets are kept. Sampling starts once interrupts are enabled, and
+    /// `hz` is clamped to [`BACKGROUND_HZ`]
+    ///
+    /// Note: each interrupt holds off all others for the ~0.3 ms it takes to convert the three
+    /// inputs, so even at the 1 kHz ceiling the main loop keeps about two thirds of the CPU
+    pub fn sample_in_background(&mut self, tc3: &TC3, hz: u16) {
+        let Some(inputs) = self.inputs.take() else {
+            return;
+        };
+        avr_device::interrupt::free(|cs| {
+            INPUTS.borrow(cs).replace(Some(inputs));
+        });
+
+        let hz = hz.clamp(*BACKGROUND_HZ.start(), *BACKGROUND_HZ.end());
+
+        // CTC with TOP = OCR3A (mode 4), clocked at 250 kHz
+        let top = (DefaultClock::FREQ / 64 / hz as u32 - 1) as u16;
+        tc3.tccr3a().write(|w| w.wgm3().set(0b00));
+        tc3.tccr3b().write(|w| {
+            w.wgm3().set(0b01);
+            w.cs3().prescale_64()
+        });
+        tc3.ocr3a().write(|w| w.set(top));
+        tc3.timsk3().write(|w| w.ocie3a().set_bit());
+    }
+
+    /// Feed every sample taken in the background since the last call through the filters, in the
+    /// order and at the times they were taken
+    pub fn collect(&mut self) {
+        while let Some((samples, taken)) =
+            avr_device::interrupt::free(|cs| QUEUE.borrow(cs).borrow_mut().pop())
+        {
+            self.feed(samples, taken);
+        }
+    }
+
+    /// Run `f` on the analog inputs, wherever they are held
+    fn with_inputs<R>(&mut self, f: impl FnOnce(&mut Inputs) -> R) -> Option<R> {
+        match self.inputs.as_mut() {
+            Some(inputs) => Some(f(inputs)),
+            None => {
+                avr_device::interrupt::free(|cs| INPUTS.borrow(cs).borrow_mut().as_mut().map(f))
+            }
         }
     }
 
     /// Take a measurement sample on all sensors
-    pub fn sample(&mut self) {
-        let coolant_sample = self.coolant_pin.analog_read(&mut self.adc);
-        let habitat_sample = self.habitat_pin.analog_read(&mut self.adc);
-        let condenser_sample = self.condenser_pin.analog_read(&mut self.adc);
+    ///
+    /// The extremes of each settled reading are tracked, and the slopes are updated every
+    /// [`SLOPE_INTERVAL`] milliseconds
+    pub fn sample(&mut self, now: u32) {
+        if let Some(samples) = self.with_inputs(Inputs::read_sensors) {
+            self.feed(samples, now);
+        }
+    }
 
-        self.coolant_temp.sample(coolant_sample, self.sens);
-        self.habitat_temp.sample(habitat_sample, self.sens);
-        self.condenser_temp.sample(condenser_sample, self.sens);
+    fn feed(&mut self, samples: [u16; 3], now: u32) {
+        let [coolant_sample, habitat_sample, condenser_sample] = samples;
 
-        if self.sens_steps > 0 {
-            self.sens *= 0.5;
-            self.sens_steps -= 1;
+        self.coolant_temp.sample(coolant_sample, now);
+        self.habitat_temp.sample(habitat_sample, now);
+        self.condenser_temp.sample(condenser_sample, now);
+
+        self.coolant_temp.track_extremes();
+        self.habitat_temp.track_extremes();
+        self.condenser_temp.track_extremes();
+
+        if now.wrapping_sub(self.last_slope) >= SLOPE_INTERVAL {
+            self.coolant_temp.track_slope(now);
+            self.habitat_temp.track_slope(now);
+            self.condenser_temp.track_slope(now);
+            self.last_slope = now;
+        }
+    }
+
+    /// Filtered ADC samples of the coolant, habitat and condenser sensors, as used to calculate
+    /// their temperatures
+    pub const fn filtered_samples(&self) -> [u16; 3] {
+        [
+            self.coolant_temp.filtered_sample() as u16,
+            self.habitat_temp.filtered_sample() as u16,
+            self.condenser_temp.filtered_sample() as u16,
+        ]
+    }
+
+    /// Read the raw ADC samples of the coolant, habitat and condenser sensors, bypassing all
+    /// filtering
+    pub fn raw_samples(&mut self) -> [u16; 3] {
+        self.with_inputs(Inputs::read_sensors).unwrap_or_default()
+    }
+
+    /// Read the raw ADC sample of an auxiliary analog input, e.g. a potentiometer or an external
+    /// 0-5V sensor
+    pub fn aux_read(&mut self, input: AuxInput) -> u16 {
+        self.with_inputs(|inputs| inputs.read_aux(input))
+            .unwrap_or_default()
+    }
+
+    /// Set the time constant of every sensor's filter once settled; see
+    /// [`Thermistor::set_time_constant`]
+    pub const fn set_time_constant(&mut self, ms: u32) {
+        self.coolant_temp.set_time_constant(ms);
+        self.habitat_temp.set_time_constant(ms);
+        self.condenser_temp.set_time_constant(ms);
+    }
+
+    /// Restart the settling of all sensors, e.g. after a probe has been swapped or rewired
+    pub const fn resettle(&mut self) {
+        self.coolant_temp.resettle();
+        self.habitat_temp.resettle();
+        self.condenser_temp.resettle();
+    }
+
+    /// Forget the lowest and highest temperatures seen so far on all sensors
+    pub const fn reset_extremes(&mut self) {
+        self.coolant_temp.reset_extremes();
+        self.habitat_temp.reset_extremes();
+        self.condenser_temp.reset_extremes();
+    }
+
+    /// Take a snapshot of all temperature readings
+    pub fn readings(&self) -> Readings {
+        Readings {
+            coolant: self.coolant_temp.temperature(),
+            habitat: self.habitat_temp.temperature(),
+            condenser: self.condenser_temp.temperature(),
+        }
+    }
+
+    /// Take the mean of each temperature over the samples since the previous call, restarting the
+    /// means; see [`Thermistor::take_mean`]
+    pub fn take_mean_readings(&mut self) -> Readings {
+        Readings {
+            coolant: self.coolant_temp.take_mean(),
+            habitat: self.habitat_temp.take_mean(),
+            condenser: self.condenser_temp.take_mean(),
         }
     }
 
@@ -92,6 +335,162 @@ impl Sensorium {
     pub const fn condenser_temp(&self) -> &Thermistor {
         &self.condenser_temp
     }
+
+    /// Access coolant temperature, e.g. to calibrate it
+    pub const fn coolant_temp_mut(&mut self) -> &mut Thermistor {
+        &mut self.coolant_temp
+    }
+
+    /// Access habitat temperature, e.g. to calibrate it
+    pub const fn habitat_temp_mut(&mut self) -> &mut Thermistor {
+        &mut self.habitat_temp
+    }
+
+    /// Access condenser temperature, e.g. to calibrate it
+    pub const fn condenser_temp_mut(&mut self) -> &mut Thermistor {
+        &mut self.condenser_temp
+    }
+}
+
+/// Analog input not used by the sensors, available through [`Sensorium::aux_read`]
+#[derive(Clone, Copy)]
+pub enum AuxInput {
+    /// `PF6`, otherwise unconnected
+    A1,
+
+    /// `PF0`, the spare thermistor input; note its bias resistor loads whatever is connected
+    A5,
+}
+
+/// Potentiometer read through one of the [`AuxInput`]s, e.g. a setpoint knob
+///
+/// Raw samples go through an IIR filter like the thermistors', and the reported position only
+/// follows the filtered sample once it has drifted more than a deadband away, so jitter of an LSB
+/// or two can't keep nudging whatever the knob drives
+pub struct Potentiometer {
+    sens: f32,
+    deadband: f32,
+    sample: f32,
+    held: f32,
+}
+
+impl Potentiometer {
+    /// Create a potentiometer filtered with the given sensitivity (0 to 1) and deadband in ADC
+    /// counts
+    #[must_use]
+    pub const fn new(sens: f32, deadband: f32) -> Self {
+        Self {
+            sens,
+            deadband,
+            sample: f32::NAN,
+            held: f32::NAN,
+        }
+    }
+
+    /// Feed a raw ADC sample of the wiper voltage
+    pub const fn sample(&mut self, value: u16) {
+        let value = u16_to_f32(value);
+        self.sample = if is_finite(self.sample) {
+            self.sample * (1.0 - self.sens) + value * self.sens
+        } else {
+            value
+        };
+
+        let drift = self.sample - self.held;
+        if !is_finite(self.held) || drift > self.deadband || drift < -self.deadband {
+            self.held = self.sample;
+        }
+    }
+
+    /// Position of the wiper in ADC counts, or NaN if never sampled
+    #[must_use]
+    pub const fn position(&self) -> f32 {
+        self.held
+    }
+}
+
+/// Snapshot of every temperature reading at one moment
+///
+/// Readings from failed sensors are NaN
+#[derive(Clone, Copy)]
+#[must_use]
+pub struct Readings {
+    /// Coolant loop temperature
+    pub coolant: Temperature,
+
+    /// Habitat (formicarium) temperature
+    pub habitat: Temperature,
+
+    /// Condenser temperature
+    pub condenser: Temperature,
+}
+
+impl Readings {
+    /// Difference in degrees celsius between the warmest and coldest readings, or `None` if any
+    /// reading is invalid
+    #[must_use]
+    pub fn spread(&self) -> Option<f32> {
+        let temps = [self.coolant, self.habitat, self.condenser].map(Temperature::as_c);
+        if !temps.iter().all(|&t| is_finite(t)) {
+            return None;
+        }
+
+        let max = temps.iter().copied().fold(f32::MIN, f32::max);
+        let min = temps.iter().copied().fold(f32::MAX, f32::min);
+        Some(max - min)
+    }
+}
+
+/// A temperature with its unit made explicit, stored in degrees celsius
+///
+/// Comparisons follow those of [`f32`], so an invalid (NaN) temperature compares unequal and
+/// unordered to everything
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[must_use]
+pub struct Temperature(f32);
+
+impl Temperature {
+    /// Invalid temperature
+    pub const NAN: Self = Self(f32::NAN);
+
+    /// Temperature from degrees celsius
+    pub const fn from_c(celsius: f32) -> Self {
+        Self(celsius)
+    }
+
+    /// Temperature from degrees fahrenheit
+    pub const fn from_f(fahrenheit: f32) -> Self {
+        Self((fahrenheit - 32.0) * (5.0 / 9.0))
+    }
+
+    /// Temperature from kelvin
+    pub const fn from_k(kelvin: f32) -> Self {
+        Self(kelvin - 273.15)
+    }
+
+    /// Value in degrees celsius
+    #[must_use]
+    pub const fn as_c(self) -> f32 {
+        self.0
+    }
+
+    /// Value in degrees fahrenheit
+    #[must_use]
+    pub const fn as_f(self) -> f32 {
+        self.0 * 1.8 + 32.0
+    }
+
+    /// Value in kelvin
+    #[must_use]
+    pub const fn as_k(self) -> f32 {
+        self.0 + 273.15
+    }
+
+    /// Checks if the temperature is a valid reading, i.e. not NaN or infinite
+    #[must_use]
+    pub const fn is_valid(self) -> bool {
+        is_finite(self.0)
+    }
 }
 
 /// Abstraction for NTC Thermistor measurement
@@ -105,13 +504,43 @@ impl Sensorium {
 /// thermistor failing open or short respectively. Invalid samples increment a counter, and if that
 /// counter exceeds a threshold, the calculated temperature will be reported as NaN until enough
 /// valid samples are taken to decrement the counter below the threshold.
+///
+/// The divider math assumes the ADC reads the supply voltage as 1023, which holds for the default
+/// 10-bit AVCC reference. With any other reference the relationship is no longer ratiometric, and
+/// [`Thermistor::set_full_scale`] must be given the reading that the supply voltage would produce.
+///
+/// Optionally, valid samples can be passed through a median filter before the IIR filter to reject
+/// single-sample spikes, such as those caused by electrical noise from relays switching.
 #[must_use]
 pub struct Thermistor {
+    r0: f32,
     b: f32,
+    r_bias: f32,
     sh_h_fixed: f32,
+    full_scale: f32,
+    offset: f32,
 
     sample: f32,
+    sens: f32,
+    time_constant: f32,
+    sample_time: u32,
+    mean_sum: f32,
+    mean_count: u16,
+    sens_steps: u8,
     kelvin: Cell<Option<f32>>,
+    fahrenheit: Cell<Option<f32>>,
+
+    median_buf: [u16; MAX_MEDIAN_WINDOW],
+    median_window: u8,
+    median_filled: u8,
+    median_idx: u8,
+
+    min_sample: f32,
+    max_sample: f32,
+
+    last_kelvin: f32,
+    last_time: u32,
+    slope: f32,
 
     bad_samples: u8,
 }
@@ -119,39 +548,295 @@ pub struct Thermistor {
 impl Thermistor {
     /// Initialize sampling and temperature calculation
     pub const fn new(r0: f32, b: f32, r_bias: f32) -> Self {
-        const INV_25C: f32 = 1.0 / (273.15 + 25.0);
+        Self::with_median(r0, b, r_bias, 1)
+    }
+
+    /// Initialize sampling and temperature calculation, feeding the median of the last `window`
+    /// valid samples into the IIR filter
+    ///
+    /// `window` must be odd and no greater than [`MAX_MEDIAN_WINDOW`]; a window of 1 disables the
+    /// median filter
+    pub const fn with_median(r0: f32, b: f32, r_bias: f32, window: u8) -> Self {
+        assert!(
+            window % 2 == 1 && window as usize <= MAX_MEDIAN_WINDOW,
+            "median window must be odd and no greater than MAX_MEDIAN_WINDOW"
+        );
         Self {
+            r0,
             b,
-            sh_h_fixed: ln(r_bias) - ln(r0) + b * INV_25C,
+            r_bias,
+            sh_h_fixed: Self::sh_h_fixed(r0, b, r_bias),
+            full_scale: 1023.0,
+            offset: 0.0,
 
             sample: 0.0,
+            sens: 1.0,
+            time_constant: 0.0,
+            sample_time: 0,
+            mean_sum: 0.0,
+            mean_count: 0,
+            sens_steps: SETTLE_STEPS,
             kelvin: Cell::new(None),
+            fahrenheit: Cell::new(None),
+
+            median_buf: [0; MAX_MEDIAN_WINDOW],
+            median_window: window,
+            median_filled: 0,
+            median_idx: 0,
+
+            min_sample: f32::INFINITY,
+            max_sample: f32::NEG_INFINITY,
+
+            last_kelvin: f32::NAN,
+            last_time: 0,
+            slope: f32::NAN,
 
             bad_samples: 0,
         }
     }
 
-    /// Sample the voltage produced by the divider circuit
+    /// Sample the voltage produced by the divider circuit, taken at `now` in milliseconds
     ///
-    /// The first sample is taken as a baseline, with the following 10 samples progressively
+    /// The first valid sample is taken as a baseline, with the following 10 samples progressively
     /// decreasing in sensitivity to quickly settle fluctuations. After that, all samples go through
-    /// a low-sensitivity IIR filter to mitigate noise
-    pub fn sample(&mut self, value: u16, sens: f32) {
-        if (8..1016).contains(&value) {
+    /// a low-sensitivity IIR filter to mitigate noise; see [`Thermistor::set_time_constant`]
+    pub fn sample(&mut self, value: u16, now: u32) {
+        if VALID_SAMPLES.contains(&value) {
             self.bad_samples = self.bad_samples.saturating_sub(1);
         } else {
             self.bad_samples = self.bad_samples.saturating_add(1);
             return;
         }
 
+        let value = self.median(value);
+
+        let sens = if self.sens_steps == 0 && self.time_constant > 0.0 {
+            let elapsed = now.wrapping_sub(self.sample_time);
+            let dt = u16_to_f32(if elapsed > u16::MAX as u32 {
+                u16::MAX
+            } else {
+                elapsed as u16
+            });
+            dt * recip(self.time_constant + dt)
+        } else {
+            self.sens
+        };
+        self.sample_time = now;
+
         self.sample = self.sample * (1.0 - sens) + u16_to_f32(value) * sens;
+        if self.sens_steps > 0 {
+            self.sens *= 0.5;
+            self.sens_steps -= 1;
+        }
+
+        if self.mean_count >= MAX_MEAN_SAMPLES {
+            self.mean_sum = 0.0;
+            self.mean_count = 0;
+        }
+        self.mean_sum += self.sample;
+        self.mean_count += 1;
+
+        self.invalidate();
+    }
+
+    /// Return the temperature of the mean filtered sample since the previous call, then restart the
+    /// mean
+    ///
+    /// This smooths over time on top of the IIR filter, at the cost of lagging by half the interval
+    /// between calls. Without any valid sample since the previous call the current temperature is
+    /// returned instead; means spanning more than [`MAX_MEAN_SAMPLES`] samples only cover the most
+    /// recent of them
+    pub fn take_mean(&mut self) -> Temperature {
+        if self.is_faulted() {
+            return Temperature::NAN;
+        }
+        if self.mean_count == 0 {
+            return self.temperature();
+        }
+
+        let mean = self.mean_sum * recip(u16_to_f32(self.mean_count));
+        self.mean_sum = 0.0;
+        self.mean_count = 0;
+
+        Temperature::from_k(self.sample_to_kelvin(mean))
+    }
+
+    /// Restart settling, taking the next valid sample as a new baseline so the filter re-converges
+    /// quickly after a step change such as a probe being swapped
+    ///
+    /// The median filter is emptied and the slope is unknown until settling completes
+    pub const fn resettle(&mut self) {
+        self.sens = 1.0;
+        self.sens_steps = SETTLE_STEPS;
+        self.median_filled = 0;
+        self.median_idx = 0;
+        self.last_kelvin = f32::NAN;
+        self.slope = f32::NAN;
+    }
+
+    /// Filter settled samples with a time constant of `ms` milliseconds instead of the fixed
+    /// sensitivity reached at the end of settling, or go back to the fixed sensitivity with 0
+    ///
+    /// Each settled sample is then weighted by `dt / (ms + dt)`, where `dt` is the time since the
+    /// previous valid sample. This is the backward Euler discretization of a first-order low-pass
+    /// filter, and close to `1 - exp(-dt / ms)` while samples are much closer together than `ms`,
+    /// so a step change is ~63% followed after `ms` however often samples are taken. The fixed
+    /// sensitivity of 1/1024 corresponds to `ms` of about 1023 sample intervals
+    pub const fn set_time_constant(&mut self, ms: u32) {
+        self.time_constant = ms as f32;
+    }
+
+    /// Checks if the baseline and settling samples have all been taken
+    #[must_use]
+    pub const fn is_settled(&self) -> bool {
+        self.sens_steps == 0
+    }
+
+    /// Replace the thermistor's coefficients, e.g. after recalibration
+    pub fn set_coefficients(&mut self, r0: f32, b: f32, r_bias: f32) {
+        self.r0 = r0;
+        self.b = b;
+        self.r_bias = r_bias;
+        self.sh_h_fixed = Self::sh_h_fixed(r0, b, r_bias);
+        self.invalidate();
+    }
+
+    /// Trim every temperature reported by a fixed number of degrees celsius, e.g. to match a
+    /// reference thermometer where a small systematic error isn't worth a full recalibration
+    ///
+    /// The trim applies on top of the coefficients, so it survives [`Thermistor::calibrate`]
+    pub fn set_offset(&mut self, celsius: f32) {
+        self.offset = celsius;
+        self.invalidate();
+    }
+
+    /// Trim added to every reported temperature, in degrees celsius
+    #[must_use]
+    pub const fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// One-point calibration: back-solve the bias resistance so that the current reading matches
+    /// `known`, e.g. with the probe in an ice bath or beside a reference thermometer, returning
+    /// whether the correction was applied
+    ///
+    /// The correction is refused unless the sensor is settled and healthy, and if the bias
+    /// resistance would change by more than a factor of ~1.6 either way, which points to the probe
+    /// not being at the reference temperature or to the wrong coefficients. The corrected value is
+    /// available from [`Thermistor::r_bias`] to be persisted and restored with
+    /// [`Thermistor::set_coefficients`]
+    pub fn calibrate(&mut self, known: Temperature) -> bool {
+        // The coefficients are solved for the reading before it is trimmed
+        let measured = self.kelvin() - self.offset;
+        let known = known.as_k() - self.offset;
+        if !self.is_settled() || !is_finite(measured) || !is_finite(known) || known <= 0.0 {
+            return false;
+        }
+
+        // The reading solves `b / kelvin = ln(full_scale / sample - 1) + sh_h_fixed`, where only
+        // `sh_h_fixed` depends on the bias resistance, through `ln(r_bias)`
+        let delta = self.b * (measured - known) * recip(measured * known);
+        if !(-0.5..=0.5).contains(&delta) {
+            return false;
+        }
+
+        self.sh_h_fixed += delta;
+        // exp(delta) by its Taylor series, good to ~0.03% over the accepted range
+        self.r_bias *=
+            1.0 + delta * (1.0 + delta * (0.5 + delta * (1.0 / 6.0 + delta * (1.0 / 24.0))));
+        self.invalidate();
+
+        true
+    }
+
+    /// The filtered ADC sample the temperature is calculated from
+    #[must_use]
+    pub const fn filtered_sample(&self) -> f32 {
+        self.sample
+    }
+
+    /// Resistance of the thermistor at 25C, in ohms
+    #[must_use]
+    pub const fn r0(&self) -> f32 {
+        self.r0
+    }
+
+    /// B coefficient of the thermistor, in kelvin
+    #[must_use]
+    pub const fn b(&self) -> f32 {
+        self.b
+    }
 
+    /// Resistance of the divider's bias resistor, in ohms
+    #[must_use]
+    pub const fn r_bias(&self) -> f32 {
+        self.r_bias
+    }
+
+    /// Set the ADC reading corresponding to the divider's supply voltage, i.e. `1023 * VCC / VREF`
+    /// for a 10-bit conversion against reference voltage `VREF`
+    ///
+    /// Defaults to 1023, which is correct when the ADC uses AVCC as its reference
+    pub fn set_full_scale(&mut self, full_scale: f32) {
+        self.full_scale = full_scale;
+        self.invalidate();
+    }
+
+    const fn sh_h_fixed(r0: f32, b: f32, r_bias: f32) -> f32 {
+        const INV_25C: f32 = 1.0 / (273.15 + 25.0);
+        ln(r_bias) - ln(r0) + b * INV_25C
+    }
+
+    /// Discard the cached temperatures; must be called whenever anything they depend on changes
+    fn invalidate(&self) {
         self.kelvin.set(None);
+        self.fahrenheit.set(None);
+    }
+
+    /// Push a valid sample into the median filter's ring buffer and return the median of its
+    /// contents
+    const fn median(&mut self, value: u16) -> u16 {
+        if self.median_window <= 1 {
+            return value;
+        }
+
+        self.median_buf[self.median_idx as usize] = value;
+        self.median_idx += 1;
+        if self.median_idx >= self.median_window {
+            self.median_idx = 0;
+        }
+        if self.median_filled < self.median_window {
+            self.median_filled += 1;
+        }
+
+        // Insertion sort a copy of the filled portion of the buffer
+        let len = self.median_filled as usize;
+        let mut sorted = self.median_buf;
+        let mut i = 1;
+        while i < len {
+            let mut j = i;
+            while j > 0 && sorted[j - 1] > sorted[j] {
+                let tmp = sorted[j];
+                sorted[j] = sorted[j - 1];
+                sorted[j - 1] = tmp;
+                j -= 1;
+            }
+            i += 1;
+        }
+
+        sorted[len / 2]
+    }
+
+    /// Checks if enough invalid samples have been taken recently that the sensor is considered to
+    /// have failed
+    #[must_use]
+    pub const fn is_faulted(&self) -> bool {
+        self.bad_samples >= 16
     }
 
     /// Return the measured temperature in kelvin
     pub fn kelvin(&self) -> f32 {
-        if self.bad_samples >= 16 {
+        if self.is_faulted() {
             return f32::NAN;
         }
 
@@ -159,13 +844,108 @@ impl Thermistor {
             return kelvin;
         }
 
-        let kelvin = self.b * recip(ln(1023.0 * recip(self.sample) - 1.0) + self.sh_h_fixed);
+        let kelvin = self.sample_to_kelvin(self.sample);
 
         self.kelvin.set(Some(kelvin));
 
         kelvin
     }
 
+    /// Trimmed temperature in kelvin of a filtered sample
+    ///
+    /// Returns NaN for filtered samples outside the valid range, which can only occur before the
+    /// first valid sample is taken, and would otherwise produce infinities or NaN from the
+    /// logarithm
+    const fn sample_to_kelvin(&self, sample: f32) -> f32 {
+        if !(sample >= VALID_SAMPLES.start as f32 && sample < VALID_SAMPLES.end as f32) {
+            return f32::NAN;
+        }
+
+        self.b * recip(ln(self.full_scale * recip(sample) - 1.0) + self.sh_h_fixed) + self.offset
+    }
+
+    /// Record the current reading if it is a new low or high; ignored until settled
+    ///
+    /// Temperature rises monotonically with the sampled voltage, so the extremes are tracked on the
+    /// raw samples and only converted to temperatures when requested
+    pub const fn track_extremes(&mut self) {
+        if self.is_faulted() || !self.is_settled() {
+            return;
+        }
+
+        if self.sample < self.min_sample {
+            self.min_sample = self.sample;
+        }
+        if self.sample > self.max_sample {
+            self.max_sample = self.sample;
+        }
+    }
+
+    /// Forget the lowest and highest temperatures seen so far
+    pub const fn reset_extremes(&mut self) {
+        self.min_sample = f32::INFINITY;
+        self.max_sample = f32::NEG_INFINITY;
+    }
+
+    /// Return the lowest temperature seen since the last reset in fahrenheit, or NaN if none has
+    /// been recorded
+    pub fn min_fahrenheit(&self) -> f32 {
+        if is_finite(self.min_sample) {
+            (self.sample_to_kelvin(self.min_sample) - 273.15) * 1.8 + 32.0
+        } else {
+            f32::NAN
+        }
+    }
+
+    /// Return the highest temperature seen since the last reset in fahrenheit, or NaN if none has
+    /// been recorded
+    pub fn max_fahrenheit(&self) -> f32 {
+        if is_finite(self.max_sample) {
+            (self.sample_to_kelvin(self.max_sample) - 273.15) * 1.8 + 32.0
+        } else {
+            f32::NAN
+        }
+    }
+
+    /// Update the rate of change using the current temperature and the one recorded on the
+    /// previous call
+    ///
+    /// The slope is NaN until two valid temperatures have been recorded since settling, or if the
+    /// current temperature is invalid
+    pub fn track_slope(&mut self, now: u32) {
+        let kelvin = if self.is_settled() {
+            self.kelvin()
+        } else {
+            f32::NAN
+        };
+        let elapsed = now.wrapping_sub(self.last_time);
+
+        self.slope = if is_finite(kelvin) && is_finite(self.last_kelvin) && elapsed > 0 {
+            let elapsed = u16_to_f32(u16::try_from(elapsed).unwrap_or(u16::MAX));
+            (kelvin - self.last_kelvin) * 1000.0 * recip(elapsed)
+        } else {
+            f32::NAN
+        };
+
+        self.last_kelvin = kelvin;
+        self.last_time = now;
+    }
+
+    /// Return the rate of change of the measured temperature in degrees celsius per second
+    pub const fn slope(&self) -> f32 {
+        self.slope
+    }
+
+    /// Return the rate of change of the measured temperature in degrees fahrenheit per second
+    pub const fn slope_fahrenheit(&self) -> f32 {
+        self.slope * 1.8
+    }
+
+    /// Return the measured temperature
+    pub fn temperature(&self) -> Temperature {
+        Temperature::from_k(self.kelvin())
+    }
+
     /// Return the measured temperature in celsius
     pub fn celsius(&self) -> f32 {
         self.kelvin() - 273.15
@@ -173,6 +953,18 @@ impl Thermistor {
 
     /// Return the measured temperature in fahrenheit
     pub fn fahrenheit(&self) -> f32 {
-        self.celsius() * 1.8 + 32.0
+        if self.is_faulted() {
+            return f32::NAN;
+        }
+
+        if let Some(fahrenheit) = self.fahrenheit.get() {
+            return fahrenheit;
+        }
+
+        let fahrenheit = self.celsius() * 1.8 + 32.0;
+
+        self.fahrenheit.set(Some(fahrenheit));
+
+        fahrenheit
     }
 }
@@ -26,6 +26,101 @@ pub const fn recip(x: f32) -> f32 {
     y * (2.0 - x * y)
 }
 
+/// Dew point in degrees celsius of air at the given temperature in degrees celsius and relative
+/// humidity in percent, using the Magnus formula
+///
+/// Accurate to within ~0.35 degrees over -45 to 60 degrees celsius; `rh` must be positive
+#[must_use]
+pub const fn dew_point(celsius: f32, rh: f32) -> f32 {
+    const B: f32 = 17.62;
+    const C: f32 = 243.12;
+
+    let gamma = ln(rh * 0.01) + B * celsius * recip(C + celsius);
+    C * gamma * recip(B - gamma)
+}
+
+/// Position of `value` within the range from `min` to `max` as a fraction clamped to 0 to 1
+///
+/// `min` may be greater than `max` to invert the range; an empty range gives 0 and a NaN `value`
+/// gives NaN
+#[must_use]
+pub const fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    let span = max - min;
+    let offset = value - min;
+    // The reciprocal approximation only holds for positive inputs, and runs slightly low, so the
+    // far end of the range is pinned to 1
+    let t = if span > 0.0 {
+        if offset >= span {
+            1.0
+        } else {
+            offset * recip(span)
+        }
+    } else if span < 0.0 {
+        if offset <= span {
+            1.0
+        } else {
+            -offset * recip(-span)
+        }
+    } else {
+        0.0
+    };
+    t.clamp(0.0, 1.0)
+}
+
+/// Value the fraction `t` of the way from `min` to `max`, the inverse of [`normalize`]
+///
+/// `t` is clamped to 0 to 1, so the result never leaves the range however it is oriented
+#[must_use]
+pub const fn denormalize(t: f32, min: f32, max: f32) -> f32 {
+    min + (max - min) * t.clamp(0.0, 1.0)
+}
+
+/// Map `value` from the range `in_min` to `in_max` onto the range `out_min` to `out_max`, clamped
+/// to the output range
+///
+/// Either range may be inverted, as for [`normalize`] and [`denormalize`]
+#[must_use]
+pub const fn remap(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
+    denormalize(normalize(value, in_min, in_max), out_min, out_max)
+}
+
+const _: () = assert!(
+    normalize(0.0, 0.0, 10.0) <= 0.0 && normalize(10.0, 0.0, 10.0) >= 1.0,
+    "the endpoints of a range must normalize to exactly 0 and 1"
+);
+const _: () = assert!(
+    normalize(0.0, 10.0, 0.0) >= 1.0 && normalize(10.0, 10.0, 0.0) <= 0.0,
+    "an inverted range must normalize to exactly 1 and 0 at its endpoints"
+);
+const _: () = assert!(
+    normalize(-1.0, 0.0, 10.0) <= 0.0 && normalize(11.0, 0.0, 10.0) >= 1.0,
+    "values outside the range must be clamped"
+);
+const _: () = assert!(
+    normalize(12.0, 10.0, 0.0) <= 0.0 && normalize(-2.0, 10.0, 0.0) >= 1.0,
+    "values outside an inverted range must be clamped"
+);
+const _: () = assert!(
+    normalize(3.0, 3.0, 3.0) <= 0.0 && normalize(4.0, 3.0, 3.0) <= 0.0,
+    "an empty range must normalize to 0"
+);
+const _: () = assert!(
+    !is_finite(normalize(f32::NAN, 0.0, 10.0)),
+    "a NaN value must stay NaN"
+);
+const _: () = assert!(
+    remap(20.0, 10.0, 20.0, 64.0, 256.0) >= 256.0 && remap(10.0, 10.0, 20.0, 64.0, 256.0) <= 64.0,
+    "the endpoints of the input range must map exactly onto those of the output range"
+);
+const _: () = assert!(
+    remap(25.0, 10.0, 20.0, 0.0, 256.0) >= 256.0 && remap(5.0, 10.0, 20.0, 0.0, 256.0) <= 0.0,
+    "values outside the input range must be clamped to the output range"
+);
+const _: () = assert!(
+    remap(5.0, 20.0, 10.0, 0.0, 256.0) >= 256.0 && remap(5.0, 10.0, 20.0, 256.0, 0.0) >= 256.0,
+    "either range may be inverted"
+);
+
 /// Efficently checks if an [`f32`] is not `Inf`, `-Inf`, or `NaN`
 #[must_use]
 pub const fn is_finite(x: f32) -> bool {
@@ -79,11 +174,22 @@ pub const fn u16_to_f32(x: u16) -> f32 {
     }
 }
 
-/// Formats an [`f32`] as a left-padded 7-byte string with 2 decimal places of precision, up to 3
-/// whole number digits, and an optional negative sign
-#[inline(never)]
+/// Formats an [`f32`] as a left-padded 7-byte string, rounded to 2 decimal places, with up to 3
+/// whole number digits and an optional negative sign
 #[must_use]
 pub const fn f32_to_bytes(value: f32) -> [u8; 7] {
+    f32_to_bytes_quantized(value, 1)
+}
+
+/// Formats an [`f32`] like [`f32_to_bytes`], rounded to the nearest multiple of `quantum`
+/// hundredths (e.g. 10 for tenths), so residual noise doesn't flicker in the last digits
+///
+/// The value is scaled to hundredths before rounding, so a carry propagates through every digit
+/// (e.g. 9.995 shows as 10.00) and float error in the fraction can't knock the last digit off by
+/// one. `quantum` must be between 1 and 100; 1 rounds to the nearest hundredth
+#[inline(never)]
+#[must_use]
+pub const fn f32_to_bytes_quantized(value: f32, quantum: u8) -> [u8; 7] {
     let [b0, b1, b2, b3] = value.to_le_bytes();
     let sign = if b3 >> 7 == 1 { b'-' } else { b' ' };
     let abs_b3 = b3 & 0x7F;
@@ -98,8 +204,13 @@ pub const fn f32_to_bytes(value: f32) -> [u8; 7] {
         }
     } else {
         let abs = f32::from_le_bytes([b0, b1, b2, abs_b3]);
-        let trunc = abs as u16;
-        let frac = ((abs - u16_to_f32(trunc)) * 100.0) as u8;
+        let quantum = quantum as u32;
+        let mut hundredths = (abs * 100.0 + u16_to_f32(quantum as u16) * 0.5) as u32;
+        hundredths -= hundredths % quantum;
+        let trunc = (hundredths / 100) as u16;
+        let frac = (hundredths % 100) as u8;
+        // Rounding may leave nothing to be negative
+        let sign = if trunc == 0 && frac == 0 { b' ' } else { sign };
         let ones = (trunc % 10) as u8;
         let tens = (trunc / 10) as u8;
 
@@ -124,6 +235,23 @@ pub const fn f32_to_bytes(value: f32) -> [u8; 7] {
     }
 }
 
+const _: () = assert!(
+    bytes_eq(&f32_to_bytes(0.005), b"    .01"),
+    "half a hundredth must round up"
+);
+const _: () = assert!(
+    bytes_eq(&f32_to_bytes(9.995), b"  10.00"),
+    "rounding must carry into the tens"
+);
+const _: () = assert!(
+    bytes_eq(&f32_to_bytes(99.995), b" 100.00"),
+    "rounding must carry into the hundreds"
+);
+const _: () = assert!(
+    bytes_eq(&f32_to_bytes(-0.004), b"    .00"),
+    "a value rounding to zero must not be negative"
+);
+
 /// Formats a [`u16`] as a left-padded 5-byte string with up to 5 whole number digits
 #[inline(never)]
 #[must_use]
@@ -188,6 +316,148 @@ pub const fn i16_to_bytes(value: i16) -> [u8; 6] {
     }
 }
 
+/// Formats a [`u32`] as a left-padded 10-byte string with up to 10 whole number digits
+#[inline(never)]
+#[must_use]
+pub const fn u32_to_bytes(value: u32) -> [u8; 10] {
+    let mut out_bytes = [b' '; 10];
+    let mut rest = value;
+    let mut i = 10;
+
+    // Always emit at least the ones digit, so zero renders as `0` rather than blank
+    loop {
+        i -= 1;
+        out_bytes[i] = b'0' + (rest % 10) as u8;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+
+    out_bytes
+}
+
+/// Formats an [`i32`] as a left-padded 11-byte string with up to 10 whole number digits and an
+/// optional negative sign
+#[inline(never)]
+#[must_use]
+pub const fn i32_to_bytes(value: i32) -> [u8; 11] {
+    let digits = u32_to_bytes(value.unsigned_abs());
+    let mut out_bytes = [b' '; 11];
+
+    let mut i = 0;
+    while i < 10 {
+        out_bytes[i + 1] = digits[i];
+        i += 1;
+    }
+
+    if value < 0 {
+        let mut lead = 1;
+        while out_bytes[lead] == b' ' {
+            lead += 1;
+        }
+        out_bytes[lead - 1] = b'-';
+    }
+
+    out_bytes
+}
+
+const _: () = assert!(
+    bytes_eq(&u32_to_bytes(0), b"         0"),
+    "zero must still print a digit"
+);
+const _: () = assert!(
+    bytes_eq(&u32_to_bytes(u32::MAX), b"4294967295"),
+    "the largest value must fill the width"
+);
+const _: () = assert!(
+    bytes_eq(&i32_to_bytes(0), b"          0"),
+    "zero must print without a sign"
+);
+const _: () = assert!(
+    bytes_eq(&i32_to_bytes(i32::MIN), b"-2147483648"),
+    "the most negative value must not overflow"
+);
+const _: () = assert!(
+    bytes_eq(&i32_to_bytes(i32::MAX), b" 2147483647"),
+    "the largest value must leave the sign position blank"
+);
+
+/// Moves the left padding of a formatted number to its right, leaving it left-justified in the
+/// same width
+#[must_use]
+pub const fn left_justify<const N: usize>(bytes: [u8; N]) -> [u8; N] {
+    let mut lead = 0;
+    while lead < N && bytes[lead] == b' ' {
+        lead += 1;
+    }
+
+    let mut out_bytes = [b' '; N];
+    let mut i = 0;
+    while lead + i < N {
+        out_bytes[i] = bytes[lead + i];
+        i += 1;
+    }
+
+    out_bytes
+}
+
+/// Formats a duration in seconds as a 10-byte hour-meter string, `DDDd HH:MM`, with the days
+/// left-padded
+///
+/// Durations of 1000 days or more saturate to `999d 23:59`
+#[inline(never)]
+#[must_use]
+pub const fn duration_to_bytes(secs: u32) -> [u8; 10] {
+    const MAX_SECS: u32 = 1000 * 86400 - 1;
+
+    let minutes = (if secs > MAX_SECS { MAX_SECS } else { secs }) / 60;
+    let days = (minutes / 1440) as u16;
+    let minute_of_day = (minutes % 1440) as u16;
+    let hours = (minute_of_day / 60) as u8;
+    let minutes = (minute_of_day % 60) as u8;
+    let tens_and_ones = (days % 100) as u8;
+
+    let mut out_bytes = [
+        b'0' + (days / 100) as u8,
+        b'0' + tens_and_ones / 10,
+        b'0' + tens_and_ones % 10,
+        b'd',
+        b' ',
+        b'0' + hours / 10,
+        b'0' + hours % 10,
+        b':',
+        b'0' + minutes / 10,
+        b'0' + minutes % 10,
+    ];
+
+    let mut i = 0;
+    while i < 2 && out_bytes[i] == b'0' {
+        out_bytes[i] = b' ';
+        i += 1;
+    }
+
+    out_bytes
+}
+
+/// Checks if two byte strings are equal, usable in const contexts unlike `==`
+#[must_use]
+pub const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
 /// Convert the lower 4 bits of a [u8] to a hexadecimal character
 ///
 /// Also works for decimal values